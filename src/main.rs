@@ -2,7 +2,7 @@ use std::{fs, path::Path};
 
 use anyhow::{anyhow, Ok, Result};
 use clap::{Parser, ValueEnum};
-use solutions::SOLUTIONS;
+use solutions::{spin_dish, Direction, SpinOptions, SOLUTIONS};
 
 use crate::{
     solutions::templates::{
@@ -33,6 +33,18 @@ struct Args {
     /// Specify the filepath to the day's input - only used when --mode is single
     #[arg(long, short)]
     input: Option<String>,
+    /// Day 14 only: the tilts that make up one spin of the dish, like NWSE
+    #[arg(long)]
+    tilts: Option<String>,
+    /// Day 14 only: how many times to spin the dish
+    #[arg(long)]
+    spins: Option<usize>,
+    /// Day 14 only: the edge to measure the load on - N, W, S or E
+    #[arg(long)]
+    edge: Option<char>,
+    /// Day 14 only: print the dish after every spin, until it starts repeating
+    #[arg(long)]
+    frames: bool,
 }
 
 fn main() {
@@ -47,7 +59,11 @@ fn main() {
             }
         }
         RunMode::Single => {
-            let result = run_single(args.day.unwrap(), args.input);
+            let result = match spin_options(&args) {
+                Result::Ok(Some(options)) => run_spin(args.input, &options),
+                Result::Ok(None) => run_single(args.day.unwrap(), args.input).map(|_| ()),
+                Err(err) => Err(err),
+            };
 
             if let Err(err) = result {
                 println!(
@@ -81,20 +97,74 @@ fn load_from_file(file_path: &Path) -> Result<String> {
     }
 }
 
+/// the input for a day, from the given path or else the default one
+fn load_day_input(day: usize, input_path: Option<String>) -> Result<String> {
+    let unwrapped_path = input_path.unwrap_or(format!("./inputs/input_{:02}.txt", day));
+    let file_path = Path::new(&unwrapped_path);
+
+    load_from_file(file_path)
+}
+
 /// run a single specified day's solution
 fn run_single(day: usize, input_path: Option<String>) -> Result<f32> {
     if day < 1 || day > SOLUTIONS.len() {
         return Err(anyhow!("Day '{}' is invalid or not yet solved", day));
     }
 
-    let unwrapped_path = input_path.unwrap_or(format!("./inputs/input_{:02}.txt", day));
-    let file_path = Path::new(&unwrapped_path);
-
-    let input = load_from_file(file_path)?;
+    let input = load_day_input(day, input_path)?;
 
     SOLUTIONS[day - 1](&input)
 }
 
+/// the options for spinning day 14's dish, if any were given. they're an error for any other day
+fn spin_options(args: &Args) -> Result<Option<SpinOptions>> {
+    if args.tilts.is_none() && args.spins.is_none() && args.edge.is_none() && !args.frames {
+        return Ok(None);
+    }
+    if args.day != Some(14) {
+        return Err(anyhow!(
+            "--tilts, --spins, --edge and --frames can only be used with day 14"
+        ));
+    }
+
+    let defaults = SpinOptions::default();
+    Ok(Some(SpinOptions {
+        tilts: match &args.tilts {
+            Some(tilts) => Direction::parse_sequence(tilts)?,
+            None => defaults.tilts,
+        },
+        spins: args.spins.unwrap_or(defaults.spins),
+        edge: match args.edge {
+            Some(edge) => Direction::try_from(edge)?,
+            None => defaults.edge,
+        },
+        frames: args.frames,
+    }))
+}
+
+/// spin day 14's dish the way the command line says, rather than running the usual solution
+fn run_spin(input_path: Option<String>, options: &SpinOptions) -> Result<()> {
+    let input = load_day_input(14, input_path)?;
+    let (load, frames) = spin_dish(&input, options)?;
+    if let Some(frames) = frames {
+        println!("{}", frames);
+    }
+
+    println!(
+        "Load on the {} edge after {} spins of {}: \t{}",
+        options.edge,
+        options.spins,
+        options
+            .tilts
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<String>(),
+        load
+    );
+
+    Ok(())
+}
+
 /// run all solutions
 fn run_all() -> Result<()> {
     let mut time_total = 0.0;
@@ -13,19 +13,14 @@ pub fn day03(input: &str) -> Result<f32> {
     solve_simultaneous::<Day03Solution, _, _, _>(input)
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Default)]
 enum Obj {
     Symbol(char),
     Number(u32),
+    #[default]
     None,
 }
 
-impl Default for Obj {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Default, Debug, Clone)]
 struct Num {
     val: u32,
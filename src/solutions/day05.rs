@@ -1,7 +1,10 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
+use crate::utils::{
+    interval::{Interval, IntervalSet},
+    solver_types::{solve_linear, SolutionLinear},
+};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day05Solution {}
@@ -10,129 +13,277 @@ pub fn day05(input: &str) -> Result<f32> {
     solve_linear::<Day05Solution, _, _, _>(input)
 }
 
-struct Guide {
-    seeds: Vec<usize>,
-    maps: Vec<Vec<[usize; 3]>>, //[destination range start, source range start, range magnitude]
+/// Part of a `Mapping`: every value in `source` moves by `shift`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    pub source: Interval<isize>,
+    pub shift: isize,
 }
 
-fn map_to_destination(num: usize, ranges: &Vec<[usize; 3]>) -> usize {
-    for r in ranges {
-        if num >= r[1] && num < r[1] + r[2] {
-            let depth = num - r[1];
-            return r[0] + depth;
-        }
-    }
-    num
+/// A piecewise function from one category's numbers to another's.
+/// Values that aren't in any piece map to themselves
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Mapping {
+    /// sorted, and never overlapping
+    pieces: Vec<Piece>,
 }
 
-fn map_range(input: [usize; 2], ranges: &Vec<[usize; 3]>) -> Vec<[usize; 2]> {
-    let mut new_ranges = vec![];
-    let mut start = input[0];
-    let end = input[1];
+#[allow(dead_code)]
+impl Mapping {
+    /// the mapping that leaves everything where it is
+    pub fn identity() -> Self {
+        Self::default()
+    }
 
-    for r in ranges {
-        if start < r[1] {
-            new_ranges.push([start, r[1] - 1]);
-            start = r[1];
+    /// build a mapping from the almanac's `[destination start, source start, length]` lines.
+    /// if two lines cover the same value, the first one wins
+    pub fn from_ranges(ranges: &[[isize; 3]]) -> Self {
+        let mut covered = IntervalSet::new();
+        let mut pieces = vec![];
+        for [dest, src, len] in ranges {
+            let source = Interval::from_len(*src, *len);
+            for s in IntervalSet::from(source).difference(&covered).intervals() {
+                pieces.push(Piece {
+                    source: *s,
+                    shift: dest - src,
+                });
+            }
+            covered.insert(source);
         }
+        Self::normalise(pieces)
+    }
 
-        if start >= r[1] && start < r[1] + r[2] - 1 {
-            if end < r[1] + r[2] {
-                let depth1 = start - r[1];
-                let depth2 = end - r[1];
-                new_ranges.push([r[0] + depth1, r[0] + depth2]);
-                start = end;
-                break;
-            } else {
-                let depth = start - r[1];
-                new_ranges.push([r[0] + depth, r[0] + r[2]]);
-                start = r[1] + r[2];
+    /// sort the pieces, dropping any that don't move anything and joining neighbours that move by the same amount
+    fn normalise(mut pieces: Vec<Piece>) -> Self {
+        pieces.retain(|p| p.shift != 0 && !p.source.is_empty());
+        pieces.sort_by_key(|p| p.source);
+        let mut merged: Vec<Piece> = vec![];
+        for p in pieces {
+            match merged.last_mut() {
+                Some(last) if last.shift == p.shift && last.source.end == p.source.start => {
+                    last.source.end = p.source.end
+                }
+                _ => merged.push(p),
             }
         }
+        Self { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// every value some piece moves
+    fn covered(&self) -> IntervalSet<isize> {
+        self.pieces.iter().map(|p| p.source).collect()
+    }
+
+    pub fn get(&self, v: isize) -> isize {
+        self.pieces
+            .iter()
+            .find(|p| p.source.contains(v))
+            .map_or(v, |p| v + p.shift)
+    }
+
+    /// where every value in the set ends up
+    pub fn image(&self, values: &IntervalSet<isize>) -> IntervalSet<isize> {
+        self.pieces
+            .iter()
+            .map(|p| values.intersection(&p.source.into()).shift(p.shift))
+            .fold(values.difference(&self.covered()), |acc, s| acc.union(&s))
     }
-    if start != end {
-        new_ranges.push([start, end]);
+
+    /// every value that ends up in the set. several values can end up in the same place, so this can be bigger than
+    /// the set itself
+    pub fn preimage(&self, values: &IntervalSet<isize>) -> IntervalSet<isize> {
+        self.pieces
+            .iter()
+            .map(|p| values.shift(-p.shift).intersection(&p.source.into()))
+            .fold(values.difference(&self.covered()), |acc, s| acc.union(&s))
+    }
+
+    /// the single mapping that does this one, and then `next`
+    pub fn then(&self, next: &Mapping) -> Mapping {
+        let mut pieces = vec![];
+        // values we move, then next may move again
+        for p in &self.pieces {
+            let image = IntervalSet::from(p.source.shift(p.shift));
+            for q in &next.pieces {
+                for i in image.intersection(&q.source.into()).intervals() {
+                    pieces.push(Piece {
+                        source: i.shift(-p.shift),
+                        shift: p.shift + q.shift,
+                    });
+                }
+            }
+            for i in image.difference(&next.covered()).intervals() {
+                pieces.push(Piece {
+                    source: i.shift(-p.shift),
+                    shift: p.shift,
+                });
+            }
+        }
+        // values we leave alone only get moved by next
+        let ours = self.covered();
+        for q in &next.pieces {
+            for i in IntervalSet::from(q.source).difference(&ours).intervals() {
+                pieces.push(Piece {
+                    source: *i,
+                    shift: q.shift,
+                });
+            }
+        }
+        Self::normalise(pieces)
     }
-    new_ranges
 }
 
-impl SolutionLinear<Guide, usize, usize> for Day05Solution {
-    fn load(input: &str) -> Result<Guide> {
-        let (seeds, maps) = input.split_once("\n\n").unwrap();
-        let seed_nums = seeds
-            .split(' ')
-            .skip(1)
-            .map(|n| n.parse().unwrap())
-            .collect();
+/// The seeds, and a map from each category to the next
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<isize>,
+    /// for each category, the category it maps to and how
+    maps: HashMap<String, (String, Mapping)>,
+}
+
+#[allow(dead_code)]
+impl Almanac {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut sections = input.split("\n\n");
+        let seeds = sections
+            .next()
+            .and_then(|s| s.strip_prefix("seeds:"))
+            .ok_or_else(|| anyhow!("expected the almanac to start with `seeds:`"))?
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
 
-        let maps_parsed = maps
-            .split("\n\n")
-            .map(|gr| {
-                gr.lines()
-                    .skip(1)
-                    .map(|l| {
-                        l.split(' ')
-                            .map(|n| n.parse().unwrap())
-                            .collect_vec()
-                            .try_into()
-                            .unwrap()
-                    })
-                    .sorted_by(|a: &[usize; 3], b: &[usize; 3]| a[1].cmp(&b[1]))
-                    .collect_vec()
-            })
-            .collect_vec();
-
-        Ok(Guide {
-            seeds: seed_nums,
-            maps: maps_parsed,
-        })
-    }
-
-    fn part1(input: &mut Guide) -> Result<usize> {
-        let mut min = usize::MAX;
-
-        for seed in &input.seeds {
-            let mut result = *seed;
-            for m in &input.maps {
-                result = map_to_destination(result, m);
+        let mut maps = HashMap::new();
+        for section in sections.filter(|s| !s.trim().is_empty()) {
+            let mut lines = section.lines();
+            let header = lines.next().unwrap_or_default();
+            let (from, to) = header
+                .strip_suffix(" map:")
+                .and_then(|h| h.split_once("-to-"))
+                .ok_or_else(|| anyhow!("expected `from-to-to map:`, got '{}'", header))?;
+            let ranges = lines
+                .map(|l| {
+                    l.split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<Vec<isize>, _>>()?
+                        .try_into()
+                        .map_err(|_| anyhow!("expected three numbers, got '{}'", l))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mapping = (to.to_string(), Mapping::from_ranges(&ranges));
+            if maps.insert(from.to_string(), mapping).is_some() {
+                return Err(anyhow!("'{}' is mapped twice", from));
             }
-            min = min.min(result);
         }
 
-        Ok(min)
+        Ok(Self { seeds, maps })
     }
 
-    fn part2(input: &mut Guide, _part_1_solution: usize) -> Result<usize> {
-        let ranges: HashSet<[usize; 2]> = input
-            .seeds
-            .chunks_exact(2)
-            .map(|c| [c[0], c[0] + c[1] - 1])
-            .collect();
-
-        Ok(input
+    /// the categories, in the order the maps chain them together, if they do form a chain
+    pub fn categories(&self) -> Option<Vec<&str>> {
+        let destinations = self.maps.values().map(|(to, _)| to.as_str()).collect_vec();
+        let mut at = self
             .maps
-            .iter()
-            // for each map, create the ranges mapped from the prior set of ranges
-            .fold(ranges, |rs, m| {
-                rs.into_iter().flat_map(|r| map_range(r, m)).collect()
-            })
-            // now find the minimum amongst those ranges
-            .into_iter()
-            .map(|[min, _]| min)
+            .keys()
+            .map(String::as_str)
+            .find(|from| !destinations.contains(from))?;
+        let mut chain = vec![at];
+        while let Some((to, _)) = self.maps.get(at) {
+            if chain.contains(&to.as_str()) {
+                return None;
+            }
+            at = to;
+            chain.push(at);
+        }
+        (chain.len() == self.maps.len() + 1).then_some(chain)
+    }
+
+    /// all the maps from one category to another, composed into one. the destination has to come later in the chain
+    pub fn mapping(&self, from: &str, to: &str) -> Result<Mapping> {
+        let mut at = from;
+        let mut visited = vec![from];
+        let mut mapping = Mapping::identity();
+        while at != to {
+            let (next, m) = self
+                .maps
+                .get(at)
+                .ok_or_else(|| anyhow!("there's no way to map '{}' to '{}'", from, to))?;
+            mapping = mapping.then(m);
+            at = next;
+            // the loop doesn't have to come back round to `from` itself
+            if visited.contains(&at) {
+                return Err(anyhow!("the maps from '{}' go round in a loop", from));
+            }
+            visited.push(at);
+        }
+        Ok(mapping)
+    }
+
+    /// map values of one category to another, in either direction along the chain.
+    /// going backwards gives every value that would map to the ones given
+    pub fn convert(
+        &self,
+        from: &str,
+        to: &str,
+        values: &IntervalSet<isize>,
+    ) -> Result<IntervalSet<isize>> {
+        match self.mapping(from, to) {
+            Ok(m) => Ok(m.image(values)),
+            Err(e) => match self.mapping(to, from) {
+                Ok(m) => Ok(m.preimage(values)),
+                Err(_) => Err(e),
+            },
+        }
+    }
+
+    /// the lowest location any of the seeds end up at
+    fn lowest_location(&self, seeds: IntervalSet<isize>) -> Result<isize> {
+        self.convert("seed", "location", &seeds)?
             .min()
-            .unwrap())
+            .ok_or_else(|| anyhow!("no seeds to plant"))
+    }
+}
+
+impl SolutionLinear<Almanac, isize, isize> for Day05Solution {
+    fn load(input: &str) -> Result<Almanac> {
+        Almanac::parse(input)
+    }
+
+    fn part1(input: &mut Almanac) -> Result<isize> {
+        input.lowest_location(
+            input
+                .seeds
+                .iter()
+                .map(|s| Interval::from_len(*s, 1))
+                .collect(),
+        )
+    }
+
+    fn part2(input: &mut Almanac, _part_1_solution: isize) -> Result<isize> {
+        input.lowest_location(
+            input
+                .seeds
+                .chunks_exact(2)
+                .map(|c| Interval::from_len(c[0], c[1]))
+                .collect(),
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day05Solution;
-    use crate::utils::solver_types::SolutionLinear;
+    use super::{Almanac, Day05Solution};
+    use crate::utils::{
+        interval::{Interval, IntervalSet},
+        solver_types::SolutionLinear,
+    };
     use rstest::rstest;
 
-    #[rstest]
-    #[case(
-        "seeds: 79 14 55 13
+    const EXAMPLE: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -165,11 +316,11 @@ temperature-to-humidity map:
 humidity-to-location map:
 60 56 37
 56 93 4
-",
-        35,
-        46
-    )]
-    fn validate(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
+";
+
+    #[rstest]
+    #[case(EXAMPLE, 35, 46)]
+    fn validate(#[case] input: &str, #[case] expected_1: isize, #[case] expected_2: isize) {
         let mut input = Day05Solution::load(input).unwrap();
         let p1 = Day05Solution::part1(&mut input).unwrap();
         assert_eq!(expected_1, p1);
@@ -177,4 +328,115 @@ humidity-to-location map:
         let p2 = Day05Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[test]
+    fn validate_categories() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+
+        assert_eq!(
+            Some(vec![
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ]),
+            almanac.categories()
+        );
+    }
+
+    #[rstest]
+    #[case("seed", "location", 79, 82)]
+    #[case("seed", "location", 14, 43)]
+    #[case("seed", "soil", 55, 57)]
+    #[case("soil", "light", 13, 34)]
+    #[case("humidity", "location", 78, 82)]
+    #[case("water", "water", 5, 5)]
+    fn validate_mapping(
+        #[case] from: &str,
+        #[case] to: &str,
+        #[case] value: isize,
+        #[case] expected: isize,
+    ) {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+
+        assert_eq!(expected, almanac.mapping(from, to).unwrap().get(value));
+    }
+
+    #[test]
+    fn validate_composition() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        let categories = almanac.categories().unwrap();
+        let composed = almanac.mapping("seed", "location").unwrap();
+
+        for seed in -10..200 {
+            let step_by_step = categories
+                .windows(2)
+                .fold(seed, |v, w| almanac.mapping(w[0], w[1]).unwrap().get(v));
+            assert_eq!(step_by_step, composed.get(seed));
+        }
+    }
+
+    #[rstest]
+    #[case(82)]
+    #[case(46)]
+    #[case(0)]
+    fn validate_reverse(#[case] location: isize) {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        let forward = almanac.mapping("seed", "location").unwrap();
+        let seeds = almanac
+            .convert("location", "seed", &Interval::from_len(location, 1).into())
+            .unwrap();
+
+        // every seed that ends up there, and nothing else
+        let brute_force: IntervalSet<isize> = (-10..200)
+            .filter(|s| forward.get(*s) == location)
+            .map(|s| Interval::from_len(s, 1))
+            .collect();
+        assert_eq!(
+            brute_force,
+            seeds.intersection(&Interval::new(-10, 200).into())
+        );
+    }
+
+    #[test]
+    fn validate_unknown_category() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+
+        assert!(almanac.mapping("seed", "nowhere").is_err());
+        assert!(almanac
+            .convert("seed", "nowhere", &IntervalSet::new())
+            .is_err());
+    }
+
+    #[test]
+    fn validate_loop() {
+        // seed leads into a loop between a and b, which never comes back to seed
+        let almanac = Almanac::parse(
+            "seeds: 1 2
+
+seed-to-a map:
+0 0 10
+
+a-to-b map:
+0 0 10
+
+b-to-a map:
+0 0 10
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            "the maps from 'seed' go round in a loop",
+            almanac.mapping("seed", "location").unwrap_err().to_string()
+        );
+        assert!(almanac
+            .convert("seed", "location", &IntervalSet::new())
+            .is_err());
+        assert_eq!(None, almanac.categories());
+    }
 }
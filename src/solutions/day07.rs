@@ -1,5 +1,7 @@
+use std::fmt::Display;
+
 use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day07Solution {}
@@ -8,115 +10,235 @@ pub fn day07(input: &str) -> Result<f32> {
     solve_linear::<Day07Solution, _, _, _>(input)
 }
 
-const RANKS: [char; 13] = [
-    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
-];
+/// The type of a hand: how many of each card it has, biggest group first.
+/// Comparing these gives the same order as the puzzle's types, and carries on working for any size of hand
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandType(pub Vec<usize>);
 
-const JOKER_RANKS: [char; 13] = [
-    'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-];
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0[..] {
+            [5] => write!(f, "five of a kind"),
+            [4, 1] => write!(f, "four of a kind"),
+            [3, 2] => write!(f, "full house"),
+            [3, 1, 1] => write!(f, "three of a kind"),
+            [2, 2, 1] => write!(f, "two pair"),
+            [2, 1, 1, 1] => write!(f, "one pair"),
+            [1, 1, 1, 1, 1] => write!(f, "high card"),
+            _ => write!(f, "groups of {}", self.0.iter().join("+")),
+        }
+    }
+}
 
-type Hand = ([char; 5], usize);
+/// How strong a hand is. Hands are compared by type, then card by card
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Evaluation {
+    pub hand_type: HandType,
+    /// the strength of each card, where higher is better
+    pub strengths: Vec<usize>,
+}
 
-fn hand_type(hand: &[char; 5]) -> usize {
-    let mut idx = [' '; 5];
-    let mut counts = [0; 5];
+/// A way of playing: the cards from strongest to weakest, the wildcards, and how many cards make a hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    order: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+}
 
-    for card in hand {
-        if let Some(j) = idx.iter().position(|c| c == card) {
-            counts[j] += 1;
-        } else {
-            let j = idx.iter().position(|c| *c == ' ').unwrap();
-            idx[j] = *card;
-            counts[j] += 1;
+#[allow(dead_code)]
+impl Rules {
+    /// `order` lists every card from strongest to weakest, and each of the `wild` cards has to be one of them
+    pub fn new(order: &str, wild: &str, hand_size: usize) -> Result<Self> {
+        let order = order.chars().collect_vec();
+        if let Some(c) = order.iter().duplicates().next() {
+            return Err(anyhow!("'{}' is in the card order twice", c));
+        }
+        let wild = wild.chars().unique().collect_vec();
+        if let Some(c) = wild.iter().find(|c| !order.contains(c)) {
+            return Err(anyhow!("wildcard '{}' isn't in the card order", c));
         }
+        if hand_size == 0 {
+            return Err(anyhow!("hands need at least one card"));
+        }
+        Ok(Self {
+            order,
+            wild,
+            hand_size,
+        })
     }
-    counts.sort();
 
-    match counts {
-        [0, 0, 0, 0, 5] => 0, // five of a kind
-        [0, 0, 0, 1, 4] => 1, // four of a kind
-        [0, 0, 0, 2, 3] => 2, // full house
-        [0, 0, 1, 1, 3] => 3, // three of a kind
-        [0, 0, 1, 2, 2] => 4, // two pair
-        [0, 1, 1, 1, 2] => 5, // one pir
-        _ => 6,               // high card
+    /// part 1's rules
+    pub fn standard() -> Self {
+        Self::new("AKQJT98765432", "", 5).unwrap()
     }
-}
 
-fn joker_hand_type(hand: &[char; 5]) -> usize {
-    // check no jokers
-    if !hand.iter().contains(&'J') {
-        hand_type(hand)
-    } else {
-        // brute force it lol - THIS ONLY ADDS 0.1ms TO THE RUNTIME ON A REAL INPUT WE STAY WINNING
-        RANKS
+    /// part 2's rules, where jokers are wild but are the weakest card on their own
+    pub fn jokers() -> Self {
+        Self::new("AKQT98765432J", "J", 5).unwrap()
+    }
+
+    fn strength(&self, card: char) -> Result<usize> {
+        self.order
+            .iter()
+            .position(|c| *c == card)
+            .map(|i| self.order.len() - 1 - i)
+            .ok_or_else(|| anyhow!("unknown card '{}'", card))
+    }
+
+    /// how many of each card that isn't wild, most common first, then strongest first
+    fn groups(&self, hand: &str) -> Result<Vec<(usize, char)>> {
+        let cards = hand.chars().collect_vec();
+        if cards.len() != self.hand_size {
+            return Err(anyhow!(
+                "'{}' has {} cards, not {}",
+                hand,
+                cards.len(),
+                self.hand_size
+            ));
+        }
+        let mut groups = cards
+            .iter()
+            .filter(|c| !self.wild.contains(c))
+            .counts()
+            .into_iter()
+            .map(|(c, n)| Ok((n, *c, self.strength(*c)?)))
+            .collect::<Result<Vec<_>>>()?;
+        groups.sort_by_key(|(n, _, strength)| std::cmp::Reverse((*n, *strength)));
+        Ok(groups.into_iter().map(|(n, c, _)| (n, c)).collect())
+    }
+
+    /// the best type a hand can be. wildcards always do best by joining the biggest group, so there's no need to try
+    /// every card in their place
+    pub fn hand_type(&self, hand: &str) -> Result<HandType> {
+        let mut counts = self.groups(hand)?.into_iter().map(|(n, _)| n).collect_vec();
+        let wild = self.hand_size - counts.iter().sum::<usize>();
+        match counts.first_mut() {
+            Some(biggest) => *biggest += wild,
+            None => counts.push(wild),
+        }
+        Ok(HandType(counts))
+    }
+
+    pub fn evaluate(&self, hand: &str) -> Result<Evaluation> {
+        Ok(Evaluation {
+            hand_type: self.hand_type(hand)?,
+            strengths: hand
+                .chars()
+                .map(|c| self.strength(c))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// why a hand is the type it is, like `T55J5 is four of a kind: 3 5s and 1 T, with 1 wild J joining the 5s`
+    pub fn explain(&self, hand: &str) -> Result<String> {
+        let groups = self.groups(hand)?;
+        let hand_type = self.hand_type(hand)?;
+        let wild = hand.chars().filter(|c| self.wild.contains(c)).collect_vec();
+
+        let mut explanation = format!("{} is {}", hand, hand_type);
+        if groups.is_empty() {
+            explanation += ": every card is wild";
+            return Ok(explanation);
+        }
+        let described = groups
+            .iter()
+            .map(|(n, c)| format!("{} {}", n, plural(*c, *n)));
+        explanation += ": ";
+        explanation += &match described.collect_vec()[..] {
+            [ref only] => only.clone(),
+            [ref rest @ .., ref last] => format!("{} and {}", rest.join(", "), last),
+            [] => unreachable!(),
+        };
+        if !wild.is_empty() {
+            explanation += &format!(
+                ", with {} wild {} joining the {}",
+                wild.len(),
+                wild.iter().join(""),
+                plural(groups[0].1, 2)
+            );
+        }
+        Ok(explanation)
+    }
+
+    /// the best type a hand can be, by trying every card in place of each wildcard
+    pub fn brute_force_hand_type(&self, hand: &str) -> Result<HandType> {
+        let cards = hand.chars().collect_vec();
+        let wild_at = (0..cards.len())
+            .filter(|i| self.wild.contains(&cards[*i]))
+            .collect_vec();
+        let plain = Self::new(&self.order.iter().collect::<String>(), "", self.hand_size)?;
+
+        wild_at
             .iter()
-            .map(|replacement| {
-                hand_type(
-                    &hand
-                        .iter()
-                        .map(|c| if c == &'J' { *replacement } else { *c })
-                        .collect_vec()
-                        .try_into()
-                        .unwrap(),
-                )
+            .map(|_| self.order.iter())
+            .multi_cartesian_product()
+            .map(|replacements| {
+                let mut replaced = cards.clone();
+                for (i, r) in wild_at.iter().zip(replacements) {
+                    replaced[*i] = *r;
+                }
+                plain.hand_type(&replaced.iter().collect::<String>())
             })
-            .min()
-            .unwrap()
+            .fold_ok(None, |best: Option<HandType>, t| best.max(Some(t)))?
+            // a hand with no wildcards has nothing to replace, so is tried as it is
+            .map_or_else(|| plain.hand_type(hand), Ok)
     }
-}
 
-fn to_ranks(hand: &[char; 5], ranks: &[char; 13]) -> [usize; 5] {
-    hand.iter()
-        .map(|c| ranks.iter().position(|r| r == c).unwrap())
-        .collect_vec()
-        .try_into()
-        .unwrap()
+    /// the total winnings, where each hand wins its bid times its rank from weakest to strongest
+    pub fn winnings(&self, hands: &[Hand]) -> Result<usize> {
+        Ok(hands
+            .iter()
+            .map(|(hand, bid)| Ok((self.evaluate(hand)?, bid)))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .sorted()
+            .zip(1..)
+            .map(|((_, bid), rank)| rank * bid)
+            .sum())
+    }
 }
 
-fn do_thing(hands: Vec<Hand>, hand_comp: fn(&[char; 5]) -> usize, rank_comp: &[char; 13]) -> usize {
-    hands
-        .iter()
-        .map(|(hand, bid)| {
-            // create a comparator index of hand type - [array of card ranks]
-            ((hand_comp(hand), to_ranks(hand, rank_comp)), hand, bid)
-        })
-        .sorted_by(|(a, _, _), (b, _, _)| b.cmp(a)) // use that comparator (note this is a reverse order comparison)
-        .zip(1..)
-        .map(|((_, _, bid), i)| i * bid)
-        .sum()
+/// `5s`, or `5` if there's only one
+fn plural(card: char, n: usize) -> String {
+    if n == 1 {
+        card.to_string()
+    } else {
+        format!("{}s", card)
+    }
 }
 
+/// the cards in a hand, and its bid
+pub type Hand = (String, usize);
+
 impl SolutionLinear<Vec<Hand>, usize, usize> for Day07Solution {
     fn load(input: &str) -> Result<Vec<Hand>> {
-        Ok(input
+        input
             .lines()
+            .filter(|l| !l.trim().is_empty())
             .map(|l| {
-                let (c, b) = l.split_once(' ').unwrap();
-                (
-                    c.chars().collect_vec().try_into().unwrap(),
-                    b.parse().unwrap(),
-                )
+                let (cards, bid) = l
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("expected `cards bid`, got '{}'", l))?;
+                Ok((cards.to_string(), bid.trim().parse()?))
             })
-            .collect())
+            .collect()
     }
 
     fn part1(input: &mut Vec<Hand>) -> Result<usize> {
-        Ok(do_thing(input.to_vec(), hand_type, &RANKS))
+        Rules::standard().winnings(input)
     }
 
     fn part2(input: &mut Vec<Hand>, _part_1_solution: usize) -> Result<usize> {
-        // do the same as part 1, but every time we get a card with a joker, brute force every type of hand it could be and pick the best
-
-        Ok(do_thing(input.to_vec(), joker_hand_type, &JOKER_RANKS))
+        Rules::jokers().winnings(input)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day07Solution;
+    use super::{Day07Solution, Rules};
     use crate::utils::solver_types::SolutionLinear;
+    use itertools::Itertools;
     use rstest::rstest;
 
     #[rstest]
@@ -139,4 +261,98 @@ QQQJA 483
         let p2 = Day07Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[rstest]
+    #[case(Rules::standard(), "32T3K", "one pair")]
+    #[case(Rules::standard(), "KTJJT", "two pair")]
+    #[case(Rules::jokers(), "KTJJT", "four of a kind")]
+    #[case(Rules::jokers(), "JJJJJ", "five of a kind")]
+    #[case(Rules::jokers(), "2345J", "one pair")]
+    #[case(Rules::new("AKQJT98765432", "J2", 5).unwrap(), "2J345", "three of a kind")]
+    #[case(Rules::new("ABC", "C", 7).unwrap(), "AABBBCC", "groups of 5+2")]
+    fn validate_hand_type(#[case] rules: Rules, #[case] hand: &str, #[case] expected: &str) {
+        assert_eq!(expected, rules.hand_type(hand).unwrap().to_string());
+    }
+
+    #[rstest]
+    #[case(
+        Rules::jokers(),
+        "T55J5",
+        "T55J5 is four of a kind: 3 5s and 1 T, with 1 wild J joining the 5s"
+    )]
+    #[case(Rules::standard(), "KK677", "KK677 is two pair: 2 Ks, 2 7s and 1 6")]
+    #[case(
+        Rules::jokers(),
+        "JJJJJ",
+        "JJJJJ is five of a kind: every card is wild"
+    )]
+    fn validate_explain(#[case] rules: Rules, #[case] hand: &str, #[case] expected: &str) {
+        assert_eq!(expected, rules.explain(hand).unwrap());
+    }
+
+    #[rstest]
+    #[case(Rules::new("AKK", "", 5), "'K' is in the card order twice")]
+    #[case(Rules::new("AK", "Q", 5), "wildcard 'Q' isn't in the card order")]
+    #[case(Rules::standard().evaluate("AAAA"), "'AAAA' has 4 cards, not 5")]
+    #[case(Rules::standard().evaluate("AAAAX"), "unknown card 'X'")]
+    fn validate_errors<T: std::fmt::Debug>(
+        #[case] result: anyhow::Result<T>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, result.unwrap_err().to_string());
+    }
+
+    /// the analytic and brute force types should agree on every hand. hands are ordered by type and then by their
+    /// cards, so they'll be ranked the same way too
+    fn check_agrees(rules: &Rules, hands: &[String]) {
+        for h in hands {
+            assert_eq!(
+                rules.brute_force_hand_type(h).unwrap(),
+                rules.hand_type(h).unwrap(),
+                "{}",
+                h
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("ABCD", "D", 1)]
+    #[case("ABCD", "D", 3)]
+    #[case("ABCD", "AD", 4)]
+    #[case("ABC", "ABC", 5)]
+    #[case("ABCD", "BD", 5)]
+    fn validate_agrees_exhaustively(#[case] order: &str, #[case] wild: &str, #[case] size: usize) {
+        let rules = Rules::new(order, wild, size).unwrap();
+        // every possible hand
+        let hands = (0..size)
+            .map(|_| order.chars())
+            .multi_cartesian_product()
+            .map(|h| h.into_iter().collect::<String>())
+            .collect_vec();
+
+        check_agrees(&rules, &hands);
+    }
+
+    #[test]
+    fn validate_agrees_on_random_hands() {
+        let rules = Rules::jokers();
+        let cards = "AKQT98765432J".chars().collect_vec();
+        // a cheap pseudo-random sequence, with jokers twice as likely as any other card
+        let mut seed: u64 = 7;
+        let hands = (0..300)
+            .map(|_| {
+                (0..5)
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        let r = (seed >> 33) as usize % (cards.len() + 1);
+                        cards[r.min(cards.len() - 1)]
+                    })
+                    .collect::<String>()
+            })
+            .collect_vec();
+
+        check_agrees(&rules, &hands);
+    }
 }
@@ -1,10 +1,11 @@
-use std::{collections::HashMap, iter::successors};
+use std::collections::HashMap;
 
 use crate::utils::{
-    maths::lcm,
+    cycle::detect,
+    maths::{align_cycles, Cycle},
     solver_types::{solve_linear, SolutionLinear},
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day08Solution {}
@@ -13,80 +14,252 @@ pub fn day08(input: &str) -> Result<f32> {
     solve_linear::<Day08Solution, _, _, _>(input)
 }
 
-// we translate strings into chars cus string comparisons are cringe
-type N = [char; 3];
-type Nodes = HashMap<N, (N, N)>;
+/// Where someone walking the network is: their node, and which instruction they'll follow next
+pub type State = (usize, usize);
 
-fn to_n(s: &str) -> N {
-    s.chars().take(3).collect_vec().try_into().unwrap()
+/// The nodes, each leading left and right to two more, and the instructions for which way to go
+#[derive(Debug, Clone)]
+pub struct Network {
+    /// true for right
+    directions: Vec<bool>,
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    links: Vec<[usize; 2]>,
 }
 
-fn traverse_while(
-    directions: Vec<char>,
-    start: N,
-    while_term: fn(&N) -> bool,
-    map: &Nodes,
-) -> usize {
-    let mut dirs = directions.iter().cycle();
-
-    successors(Some(start), |n| {
-        let (l, r) = map.get(n).unwrap();
-        match dirs.next().unwrap() {
-            'L' => Some(*l),
-            'R' => Some(*r),
-            _ => panic!("unexpected direction"),
-        }
-    })
-    .take_while(while_term)
-    .count()
+/// Every step at which a walk through the network lands on an end node.
+/// After `tail` steps the walk loops round every `period` steps, so this covers every step there will ever be
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub tail: usize,
+    pub period: usize,
+    /// the steps before the walk starts to repeat that land on an end node. the ones from `tail` onwards come round
+    /// again every period
+    pub ends: Vec<usize>,
 }
 
-impl SolutionLinear<(Vec<char>, Nodes), usize, u128> for Day08Solution {
-    fn load(input: &str) -> Result<(Vec<char>, Nodes)> {
-        let (dirs, map) = input.split_once("\n\n").unwrap();
+#[allow(dead_code)]
+impl Path {
+    pub fn at_end(&self, step: usize) -> bool {
+        let step = if step < self.tail {
+            step
+        } else {
+            self.tail + (step - self.tail) % self.period
+        };
+        self.ends.binary_search(&step).is_ok()
+    }
 
-        let parsed_map = map.lines().fold(HashMap::new(), |mut hm, l| {
-            // x -> (y,z)
-            let (x, yz) = l.split_once(" = (").unwrap(); // doing silly splits here cus cant be bothered to regex - the game awards bombed my sleep schedule
-            let (y, z) = yz.split_once(", ").unwrap();
+    /// the ends we only pass through once, on the way into the loop
+    pub fn tail_ends(&self) -> &[usize] {
+        &self.ends[..self.ends.partition_point(|e| *e < self.tail)]
+    }
 
-            let _ = hm.insert(to_n(x), (to_n(y), to_n(z)));
-            hm
-        });
+    /// each end inside the loop, as a cycle of the steps that land on it
+    pub fn cycles(&self) -> Vec<Cycle> {
+        self.ends[self.tail_ends().len()..]
+            .iter()
+            .map(|e| Cycle::new(*e as i128, self.period as i128))
+            .collect()
+    }
+}
 
-        Ok((dirs.chars().collect_vec(), parsed_map))
+#[allow(dead_code)]
+impl Network {
+    /// load the instructions, then lines like `AAA = (BBB, CCC)`. names can be any length
+    pub fn parse(input: &str) -> Result<Self> {
+        let (dirs, map) = input
+            .split_once("\n\n")
+            .ok_or_else(|| anyhow!("expected instructions and nodes separated by a blank line"))?;
+        let directions = dirs
+            .trim()
+            .chars()
+            .map(|c| match c {
+                'L' => Ok(false),
+                'R' => Ok(true),
+                _ => Err(anyhow!("unexpected direction '{}'", c)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if directions.is_empty() {
+            return Err(anyhow!("there are no instructions"));
+        }
+
+        let mut declared = vec![];
+        for l in map.lines().filter(|l| !l.trim().is_empty()) {
+            let (name, lr) = l
+                .split_once(" = ")
+                .and_then(|(n, lr)| {
+                    Some((n.trim(), lr.trim().strip_prefix('(')?.strip_suffix(')')?))
+                })
+                .and_then(|(n, lr)| Some((n, lr.split_once(", ")?)))
+                .ok_or_else(|| anyhow!("expected `node = (left, right)`, got '{}'", l))?;
+            declared.push((name, lr));
+        }
+
+        let mut ids = HashMap::new();
+        for (i, (name, _)) in declared.iter().enumerate() {
+            if ids.insert(name.to_string(), i).is_some() {
+                return Err(anyhow!("node '{}' is declared twice", name));
+            }
+        }
+        let id = |from: &str, to: &str| {
+            ids.get(to)
+                .copied()
+                .ok_or_else(|| anyhow!("node '{}' leads to '{}', which doesn't exist", from, to))
+        };
+        let links = declared
+            .iter()
+            .map(|(name, (l, r))| Ok([id(name, l)?, id(name, r)?]))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            directions,
+            names: declared.iter().map(|(n, _)| n.to_string()).collect(),
+            ids,
+            links,
+        })
     }
 
-    fn part1((dirs, nodes): &mut (Vec<char>, Nodes)) -> Result<usize> {
-        Ok(traverse_while(
-            dirs.clone(),
-            ['A', 'A', 'A'],
-            |n| n != &['Z', 'Z', 'Z'],
-            nodes,
-        ))
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
     }
 
-    fn part2((dirs, nodes): &mut (Vec<char>, Nodes), _part_1_solution: usize) -> Result<u128> {
-        // parse out a's
-        Ok(
-            nodes
-                .keys()
-                .filter(|[_, _, c]| c == &'A')
-                .map(|start| {
-                    traverse_while(dirs.clone(), *start, |[_, _, c]| c != &'Z', nodes) as u128
-                })
-                .reduce(lcm)
-                .unwrap(), // its the lowest common multiple babey!!!!
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    /// every node whose name matches
+    pub fn nodes_where(&self, matches: impl Fn(&str) -> bool) -> Vec<usize> {
+        (0..self.names.len())
+            .filter(|i| matches(&self.names[*i]))
+            .collect()
+    }
+
+    pub fn step(&self, (node, instr): State) -> State {
+        (
+            self.links[node][self.directions[instr] as usize],
+            (instr + 1) % self.directions.len(),
         )
     }
+
+    /// walk from a node until we're somewhere we've been before with the same instruction next, noting every time
+    /// we land on an end node on the way
+    pub fn analyse(&self, start: usize, is_end: impl Fn(&str) -> bool) -> Path {
+        let history = detect((start, 0), |s| self.step(*s), |s| *s);
+        Path {
+            tail: history.repeat.offset,
+            period: history.repeat.period,
+            ends: history
+                .states()
+                .iter()
+                .positions(|(n, _)| is_end(&self.names[*n]))
+                .collect(),
+        }
+    }
+
+    /// the number of steps until we first land on an end node
+    pub fn first_arrival(&self, start: &str, is_end: impl Fn(&str) -> bool) -> Result<usize> {
+        let id = self
+            .id(start)
+            .ok_or_else(|| anyhow!("there's no node called '{}'", start))?;
+        self.analyse(id, is_end)
+            .ends
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("starting from '{}' never reaches an end node", start))
+    }
+
+    /// the first step where everyone walking from the starts is on an end node at the same time
+    pub fn simultaneous_arrival(
+        &self,
+        starts: &[usize],
+        is_end: impl Fn(&str) -> bool,
+    ) -> Result<u128> {
+        let paths = starts
+            .iter()
+            .map(|s| self.analyse(*s, &is_end))
+            .collect_vec();
+        if let Some((s, _)) = starts.iter().zip(&paths).find(|(_, p)| p.ends.is_empty()) {
+            return Err(anyhow!(
+                "starting from '{}' never reaches an end node",
+                self.name(*s)
+            ));
+        }
+        let Some(longest) = paths.iter().max_by_key(|p| p.tail) else {
+            return Err(anyhow!("there's nobody walking"));
+        };
+
+        // before the last walk settles into its loop, any meeting has to be one of its one-off ends
+        if let Some(t) = longest
+            .tail_ends()
+            .iter()
+            .find(|t| paths.iter().all(|p| p.at_end(**t)))
+        {
+            return Ok(*t as u128);
+        }
+
+        // after that, every walk is looping, so try lining up each combination of the ends in their loops
+        paths
+            .iter()
+            .map(|p| p.cycles())
+            .multi_cartesian_product()
+            .filter_map(align_cycles)
+            .min()
+            .map(|t| t as u128)
+            .ok_or_else(|| {
+                anyhow!(
+                    "the walks never line up: {}",
+                    starts
+                        .iter()
+                        .zip(&paths)
+                        .map(|(s, p)| format!(
+                            "from '{}' the ends are at steps {:?} then every {} steps after {}",
+                            self.name(*s),
+                            p.ends,
+                            p.period,
+                            p.tail
+                        ))
+                        .join("; ")
+                )
+            })
+    }
+}
+
+impl SolutionLinear<Network, usize, u128> for Day08Solution {
+    fn load(input: &str) -> Result<Network> {
+        Network::parse(input)
+    }
+
+    fn part1(network: &mut Network) -> Result<usize> {
+        network.first_arrival("AAA", |n| n == "ZZZ")
+    }
+
+    fn part2(network: &mut Network, _part_1_solution: usize) -> Result<u128> {
+        // each ghost reaches its end node after some run-up, then loops round to it again.
+        // they don't all loop from the start, or only pass one end node, so line up everywhere they could meet
+        network.simultaneous_arrival(&network.nodes_where(|n| n.ends_with('A')), |n| {
+            n.ends_with('Z')
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day08Solution;
+    use super::{Day08Solution, Network, Path};
     use crate::utils::solver_types::SolutionLinear;
     use rstest::rstest;
 
+    /// walk everyone one step at a time until they're all on an end node
+    fn brute_force(network: &Network, starts: &[usize], limit: usize) -> Option<u128> {
+        let mut states = starts.iter().map(|s| (*s, 0)).collect::<Vec<_>>();
+        for t in 0..limit {
+            if states.iter().all(|(n, _)| network.name(*n).ends_with('Z')) {
+                return Some(t as u128);
+            }
+            states = states.iter().map(|s| network.step(*s)).collect();
+        }
+        None
+    }
+
     #[rstest]
     #[case(
         "RL
@@ -127,6 +300,24 @@ XXX = (XXX, XXX)
         2,
         6
     )]
+    // the ghosts have different run-ups before they start looping, so the lcm alone is wrong
+    #[case(
+        "L
+
+AAA = (ZZZ, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+",
+        1,
+        4
+    )]
     fn validate(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: u128) {
         let mut input = Day08Solution::load(input).unwrap();
 
@@ -136,4 +327,137 @@ XXX = (XXX, XXX)
         let p2 = Day08Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[test]
+    fn validate_analyse() {
+        let network = Network::parse(
+            "LR
+
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+        )
+        .unwrap();
+        let path = network.analyse(network.id("22A").unwrap(), |n| n.ends_with('Z'));
+
+        // 22B is next visited with the same instruction after 6 more steps
+        assert_eq!(
+            Path {
+                tail: 1,
+                period: 6,
+                ends: vec![3, 6]
+            },
+            path
+        );
+        assert!(path.tail_ends().is_empty());
+        assert!((0..30).all(|t| path.at_end(t) == (t > 0 && t % 3 == 0)));
+    }
+
+    #[test]
+    fn validate_long_names() {
+        let network = Network::parse(
+            "LR
+
+START = (MIDDLE, MIDDLE)
+MIDDLE = (START, FINISH)
+FINISH = (FINISH, FINISH)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Ok(2),
+            network
+                .first_arrival("START", |n| n == "FINISH")
+                .map_err(|e| e.to_string())
+        );
+    }
+
+    #[rstest]
+    // they're both only at an end once, before they start looping
+    #[case(
+        "L
+
+1A = (1Z, 1Z)
+1Z = (1B, 1B)
+1B = (1B, 1B)
+2A = (2Z, 2Z)
+2Z = (2B, 2B)
+2B = (2C, 2C)
+2C = (2B, 2B)",
+        Some(1)
+    )]
+    // the first loops through two ends, and the walks meet at the second of them first
+    #[case(
+        "L
+
+1A = (1B, 1B)
+1B = (1Z, 1Z)
+1Z = (1YZ, 1YZ)
+1YZ = (1C, 1C)
+1C = (1B, 1B)
+2A = (2B, 2B)
+2B = (2C, 2C)
+2C = (2Z, 2Z)
+2Z = (2B, 2B)",
+        Some(3)
+    )]
+    // loops of 2 and 4 steps that are always out of step
+    #[case(
+        "L
+
+1A = (1Z, 1Z)
+1Z = (1B, 1B)
+1B = (1Z, 1Z)
+2A = (2B, 2B)
+2B = (2C, 2C)
+2C = (2D, 2D)
+2D = (2Z, 2Z)
+2Z = (2B, 2B)",
+        None
+    )]
+    fn validate_simultaneous_arrival(#[case] input: &str, #[case] expected: Option<u128>) {
+        let network = Network::parse(input).unwrap();
+        let starts = network.nodes_where(|n| n.ends_with('A'));
+        let found = network.simultaneous_arrival(&starts, |n| n.ends_with('Z'));
+
+        assert_eq!(expected, found.as_ref().ok().copied());
+        assert_eq!(expected, brute_force(&network, &starts, 1000));
+        if expected.is_none() {
+            assert!(found
+                .unwrap_err()
+                .to_string()
+                .starts_with("the walks never line up"));
+        }
+    }
+
+    #[rstest]
+    #[case("LX\n\nAAA = (AAA, AAA)", "unexpected direction 'X'")]
+    #[case(
+        "L\n\nAAA = (BBB, AAA)",
+        "node 'AAA' leads to 'BBB', which doesn't exist"
+    )]
+    #[case("L\n\nAAA = BBB", "expected `node = (left, right)`, got 'AAA = BBB'")]
+    #[case(
+        "L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)",
+        "node 'AAA' is declared twice"
+    )]
+    fn validate_parse_errors(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, Network::parse(input).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn validate_never_arrives() {
+        let network =
+            Network::parse("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").unwrap();
+
+        assert_eq!(
+            "starting from 'AAA' never reaches an end node",
+            network
+                .first_arrival("AAA", |n| n == "ZZZ")
+                .unwrap_err()
+                .to_string()
+        );
+    }
 }
@@ -1,4 +1,7 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::utils::{
+    maths::extrapolate,
+    solver_types::{solve_linear, SolutionLinear},
+};
 use anyhow::Result;
 use itertools::Itertools;
 
@@ -9,32 +12,8 @@ pub fn day09(input: &str) -> Result<f32> {
     solve_linear::<Day09Solution, _, _, _>(input)
 }
 
-fn extrapolate(seq: &[isize], forward: bool) -> isize {
-    let mut reduced = seq.to_owned();
-    if !forward {
-        reduced.reverse();
-    }
-    let mut next = *reduced.last().unwrap();
-
-    while reduced.iter().any(|v| *v != 0) {
-        reduced = reduced
-            .windows(2)
-            .map(|v| {
-                if v.len() != 2 {
-                    panic!("invalid sequence")
-                } else {
-                    v[1] - v[0]
-                }
-            })
-            .collect_vec();
-        next += reduced.last().unwrap();
-    }
-
-    next
-}
-
-impl SolutionLinear<Vec<Vec<isize>>, isize, isize> for Day09Solution {
-    fn load(input: &str) -> Result<Vec<Vec<isize>>> {
+impl SolutionLinear<Vec<Vec<i128>>, i128, i128> for Day09Solution {
+    fn load(input: &str) -> Result<Vec<Vec<i128>>> {
         Ok(input
             .lines()
             .map(|l| {
@@ -45,12 +24,12 @@ impl SolutionLinear<Vec<Vec<isize>>, isize, isize> for Day09Solution {
             .collect_vec())
     }
 
-    fn part1(input: &mut Vec<Vec<isize>>) -> Result<isize> {
-        Ok(input.iter().map(|v| extrapolate(v, true)).sum())
+    fn part1(input: &mut Vec<Vec<i128>>) -> Result<i128> {
+        Ok(input.iter().map(|v| extrapolate(v, v.len() as i128)).sum())
     }
 
-    fn part2(input: &mut Vec<Vec<isize>>, _part_1_solution: isize) -> Result<isize> {
-        Ok(input.iter().map(|v| extrapolate(v, false)).sum())
+    fn part2(input: &mut Vec<Vec<i128>>, _part_1_solution: i128) -> Result<i128> {
+        Ok(input.iter().map(|v| extrapolate(v, -1)).sum())
     }
 }
 
@@ -69,7 +48,7 @@ mod tests {
         114,
         2
     )]
-    fn validate(#[case] input: &str, #[case] expected_1: isize, #[case] expected_2: isize) {
+    fn validate(#[case] input: &str, #[case] expected_1: i128, #[case] expected_2: i128) {
         let mut input = Day09Solution::load(input).unwrap();
 
         let p1 = Day09Solution::part1(&mut input).unwrap();
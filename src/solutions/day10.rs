@@ -1,16 +1,17 @@
 use std::{collections::HashMap, convert::identity};
 
 use crate::utils::{
+    geometry::interior_points,
     grid::Grid,
-    load_input::load_2d_grid,
+    load_input::{load_2d_grid_with, GridOptions},
     point::Pt,
     solver_types::{solve_linear, SolutionLinear},
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 
-// implementation based on non-zero winding as described here: https://old.reddit.com/r/adventofcode/comments/18eza5g/2023_day_10_animated_visualization/kcqwjon/
+// to find the enclosed area, we treat the loop as a polygon and count the tiles inside it with the shoelace formula and Pick's theorem
 pub struct Day10Solution {}
 
 pub fn day10(input: &str) -> Result<f32> {
@@ -32,14 +33,6 @@ lazy_static! {
 
 type Maze = Grid<char, 2>;
 
-fn find_start(maze: &Maze) -> Pt<2> {
-    *maze
-        .grid
-        .iter()
-        .find_map(|(k, &v)| if v == 'S' { Some(k) } else { None })
-        .unwrap()
-}
-
 /// check a point (to) has an exit to a point (from)
 fn can_enter(maze: &Maze, from: &Pt<2>, to: &Pt<2>) -> bool {
     let to_shape = match maze.grid.get(to) {
@@ -63,9 +56,9 @@ fn adjs(maze: &Maze, pt: &Pt<2>) -> Vec<Pt<2>> {
         .collect_vec()
 }
 
-fn measure_loop(maze: &Maze, start: &Pt<2>) -> (HashMap<Pt<2>, isize>, isize) {
-    // notate distances of points in loop from start
-    let mut distances: HashMap<Pt<2>, isize> = HashMap::from([(*start, 1)]);
+/// the points of the loop in the order they're visited, starting from `start`
+fn trace_loop(maze: &Maze, start: &Pt<2>) -> Vec<Pt<2>> {
+    let mut path = vec![*start];
 
     // find start
     let mut ptr = [Pt([0, -1]), Pt([1, 0]), Pt([0, 1]), Pt([-1, 0])]
@@ -73,63 +66,42 @@ fn measure_loop(maze: &Maze, start: &Pt<2>) -> (HashMap<Pt<2>, isize>, isize) {
         .map(|offset| (*start, offset + start))
         .find(|(_, to)| can_enter(maze, start, to))
         .unwrap();
-    let mut d = 2;
 
     while ptr.1 != *start {
-        // note distance if shorter
-        match distances.get(&ptr.1) {
-            Some(dist) => distances.insert(ptr.1, *dist.min(&d)),
-            None => distances.insert(ptr.1, d),
-        };
+        path.push(ptr.1);
         // get next step
         ptr = (
             ptr.1,
             *adjs(maze, &ptr.1).iter().find(|p| **p != ptr.0).unwrap(),
         );
-        d += 1;
     }
-    (distances, d - 1)
+    path
 }
 
-impl SolutionLinear<Maze, isize, usize> for Day10Solution {
-    fn load(input: &str) -> Result<Maze> {
-        Ok(load_2d_grid(input, identity))
+impl SolutionLinear<(Maze, Pt<2>), usize, usize> for Day10Solution {
+    fn load(input: &str) -> Result<(Maze, Pt<2>)> {
+        let (maze, symbols) = load_2d_grid_with(
+            input,
+            identity,
+            &GridOptions {
+                ignore: ".",
+                symbols: "S",
+                ..Default::default()
+            },
+        );
+        let start = symbols
+            .get(&'S')
+            .and_then(|s| s.first())
+            .ok_or_else(|| anyhow!("there's no S to start the loop from"))?;
+        Ok((maze, *start))
     }
 
-    fn part1(input: &mut Maze) -> Result<isize> {
-        Ok(measure_loop(input, &find_start(input)).1 / 2)
+    fn part1((maze, start): &mut (Maze, Pt<2>)) -> Result<usize> {
+        Ok(trace_loop(maze, start).len() / 2)
     }
 
-    fn part2(input: &mut Maze, _part_1_solution: isize) -> Result<usize> {
-        let ([_, _], [max_x, _]) = input.bounds();
-        let (loop_steps, size) = measure_loop(input, &find_start(input));
-        let mut contained = 0;
-        let mut inside = 0;
-
-        for pt in input.iter_linear() {
-            let below = pt + &Pt([0, 1]);
-            let steps = loop_steps
-                .get(pt)
-                .and_then(|st1| loop_steps.get(&below).map(|st2| st1 - st2 % size));
-
-            if pt.0[0] == max_x {
-                inside = 0;
-            } else {
-                match steps {
-                    Some(v) => {
-                        if v.abs() == 1 {
-                            inside += v
-                        }
-                    }
-                    _ => {
-                        if inside != 0 && !loop_steps.contains_key(pt) {
-                            contained += 1;
-                        }
-                    }
-                }
-            }
-        }
-        Ok(contained)
+    fn part2((maze, start): &mut (Maze, Pt<2>), _part_1_solution: usize) -> Result<usize> {
+        Ok(interior_points(&trace_loop(maze, start)) as usize)
     }
 }
 
@@ -204,7 +176,7 @@ L7JLJL-JLJLJL--JLJ.L
         80,
         10
     )]
-    fn validate_day10(#[case] input: &str, #[case] expected_1: isize, #[case] expected_2: usize) {
+    fn validate_day10(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
         let mut input = Day10Solution::load(input).unwrap();
 
         let p1 = Day10Solution::part1(&mut input).unwrap();
@@ -213,4 +185,9 @@ L7JLJL-JLJLJL--JLJ.L
         let p2 = Day10Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[test]
+    fn validate_no_start() {
+        assert!(Day10Solution::load(".F-7.\n.|.|.\n.L-J.").is_err());
+    }
 }
@@ -54,13 +54,8 @@ fn do_thing(input: &[Pt<2>], expansion: isize) -> usize {
 
     let ixs = (0..expanded.len()).flat_map(|x| (x + 1..expanded.len()).map(move |y| (x, y)));
 
-    ixs.map(|(a, b)| {
-        let Pt([ax, ay]) = expanded.get(a).unwrap();
-        let Pt([bx, by]) = expanded.get(b).unwrap();
-
-        ((ax.max(bx) - ax.min(bx)) + (ay.max(by) - ay.min(by))) as usize
-    })
-    .sum()
+    ixs.map(|(a, b)| expanded[a].manhattan(&expanded[b]) as usize)
+        .sum()
 }
 
 impl SolutionLinear<Vec<Pt<2>>, usize, usize> for Day11Solution {
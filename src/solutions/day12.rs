@@ -1,23 +1,24 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::fmt::{Debug, Display};
 
 use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::{repeat_n, Itertools};
 
-// very messy dynamic approach - essentially, recur from 0 to end of a gear list:
-// if we've fit the pattern, return 1
-// if we cannot finish the pattern, return 0
-// if we hit a #, make sure it and subsequent #'s fits the pattern else return 0
-// when we hit a ?, recur down two branches: one for the ? being a #, one for the ? being a .
-// we only memoize interesting paths (branching), but this brings the runtime down from possibly hours to 31ms on a real input!
+// dynamic programming, a spring at a time: the only thing that matters about the springs we've already placed is how
+// many runs of broken springs we've finished, and how long the run we're in the middle of is. so we keep a count of
+// the ways of reaching each of those states, and push the counts forward through each spring. with a couple of
+// buffers that get reused from row to row, a real input is counted without allocating anything per spring
 pub struct Day12Solution {}
 
 pub fn day12(input: &str) -> Result<f32> {
     solve_linear::<Day12Solution, _, _, _>(input)
 }
 
+/// how many copies of each row part 2 joins together
+const UNFOLD_FACTOR: usize = 5;
+
 #[derive(PartialEq, Eq, Clone, Copy)]
-enum Gear {
+pub enum Gear {
     Operational,
     Broken,
     Unknown,
@@ -37,137 +38,268 @@ impl Debug for Gear {
     }
 }
 
-// check if a run of broken can fit starting from `start`
-fn fits_run(gears: &[Gear], start: usize, run: usize) -> bool {
-    for off in 0..run {
-        if gears[start + off] == Gear::Operational {
-            return false;
+impl Gear {
+    /// whether the gear could be broken, or could be working
+    fn options(&self) -> &'static [bool] {
+        match self {
+            Gear::Operational => &[false],
+            Gear::Broken => &[true],
+            Gear::Unknown => &[false, true],
         }
     }
+}
 
-    if start + run >= gears.len() {
-        return true;
+/// How far through a row's runs we are: the number of runs finished, and the length of the run we're in
+type State = (usize, usize);
+
+/// the state after one more gear, if the runs still allow it
+fn advance(runs: &[usize], (group, run): State, broken: bool) -> Option<State> {
+    if broken {
+        (group < runs.len() && run < runs[group]).then_some((group, run + 1))
+    } else if run == 0 {
+        Some((group, 0))
+    } else {
+        (runs.get(group) == Some(&run)).then_some((group + 1, 0))
     }
-    gears[start + run] != Gear::Broken
 }
 
-fn recursive_solve(
-    gears: &[Gear],
-    memo: &mut HashMap<(usize, Vec<usize>), usize>,
-    idx: usize,
-    remaining_runs: Vec<usize>,
-) -> usize {
-    if let Some(v) = memo.get(&(idx, remaining_runs.clone())) {
-        return *v;
-    }
-
-    // if we're out of gears but have runs remaining, fail
-    if idx >= gears.len() {
-        if remaining_runs.is_empty() {
-            return 1;
-        } else {
-            return 0;
+/// whether every run has been placed
+fn is_done(runs: &[usize], (group, run): State) -> bool {
+    (group == runs.len() && run == 0) || (group + 1 == runs.len() && run == runs[group])
+}
+
+/// A row of gears, and the lengths of the runs of broken ones in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub gears: Vec<Gear>,
+    pub runs: Vec<usize>,
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for g in &self.gears {
+            write!(f, "{:?}", g)?;
         }
+        write!(f, " {}", self.runs.iter().join(","))
+    }
+}
+
+#[allow(dead_code)]
+impl Row {
+    /// load a line like `???.### 1,1,3`
+    pub fn parse(line: &str) -> Result<Self> {
+        let (gears, runs) = line
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("expected `gears runs`, got '{}'", line))?;
+        Ok(Self {
+            gears: gears
+                .chars()
+                .map(|c| match c {
+                    '.' => Ok(Gear::Operational),
+                    '#' => Ok(Gear::Broken),
+                    '?' => Ok(Gear::Unknown),
+                    _ => Err(anyhow!("unexpected gear '{}'", c)),
+                })
+                .collect::<Result<_>>()?,
+            runs: runs
+                .split(',')
+                .map(|v| v.trim().parse())
+                .collect::<Result<_, _>>()?,
+        })
     }
 
-    if remaining_runs.is_empty() {
-        // if we're out of runs, check if there's any gears left
-        if gears[idx] == Gear::Broken {
-            return 0;
+    /// `factor` copies of the row, with an unknown gear between each
+    pub fn unfold(&self, factor: usize) -> Self {
+        #[allow(unstable_name_collisions)]
+        let gears = repeat_n(&self.gears, factor)
+            .intersperse(&vec![Gear::Unknown])
+            .flatten()
+            .copied()
+            .collect_vec();
+        Self {
+            gears,
+            runs: self.runs.repeat(factor),
         }
-        return recursive_solve(gears, memo, idx + 1, remaining_runs);
     }
 
-    if remaining_runs.iter().sum::<usize>() > gears.len() - idx {
-        // if we dont have enough gears to satisfy the runs
-        return 0;
+    /// the number of ways the unknown gears could be filled in
+    pub fn count(&self) -> usize {
+        Counter::default().count(self)
+    }
+
+    /// for each position, and each state we could be in there, how many ways there are to finish the row.
+    /// states are numbered `group * width + run`, and the width is returned alongside
+    fn completions(&self) -> (Vec<Vec<usize>>, usize) {
+        let width = self.runs.iter().max().unwrap_or(&0) + 1;
+        let states = (self.runs.len() + 1) * width;
+        let state = |i: usize| (i / width, i % width);
+
+        let mut ways = vec![vec![0; states]; self.gears.len() + 1];
+        for (i, w) in ways[self.gears.len()].iter_mut().enumerate() {
+            *w = is_done(&self.runs, state(i)) as usize;
+        }
+        for pos in (0..self.gears.len()).rev() {
+            for i in 0..states {
+                ways[pos][i] = self.gears[pos]
+                    .options()
+                    .iter()
+                    .filter_map(|b| advance(&self.runs, state(i), *b))
+                    .map(|(g, r)| ways[pos + 1][g * width + r])
+                    .sum();
+            }
+        }
+        (ways, width)
     }
 
-    // if we have runs,
-    match gears[idx] {
-        Gear::Operational => recursive_solve(gears, memo, idx + 1, remaining_runs), // just continue
-        Gear::Broken => {
-            // if we can fit the next run here, continue. otherwise, fail
-            let (run, runs) = remaining_runs.split_first().unwrap();
-            if fits_run(gears, idx, *run) {
-                recursive_solve(gears, memo, idx + run + 1, runs.to_vec())
-            } else {
-                0
+    /// the nth way to fill in the row, read off a table from `completions`. n has to be less than `ways[0][0]`
+    fn walk(&self, ways: &[Vec<usize>], width: usize, mut n: usize) -> String {
+        let mut state = (0, 0);
+        let mut arrangement = String::with_capacity(self.gears.len());
+        for (pos, gear) in self.gears.iter().enumerate() {
+            for broken in gear.options() {
+                let Some(next) = advance(&self.runs, state, *broken) else {
+                    continue;
+                };
+                let here = ways[pos + 1][next.0 * width + next.1];
+                if n < here {
+                    arrangement.push(if *broken { '#' } else { '.' });
+                    state = next;
+                    break;
+                }
+                n -= here;
             }
         }
-        Gear::Unknown => {
-            // branch - either resolve to `.` or start new run
-            // if we can fit the next run here, continue. otherwise, fail
-            let (run, runs) = remaining_runs.split_first().unwrap();
-            let is_hash = if fits_run(gears, idx, *run) {
-                recursive_solve(gears, memo, idx + run + 1, runs.to_vec())
-            } else {
-                0
-            };
-
-            let res = is_hash + recursive_solve(gears, memo, idx + 1, remaining_runs.clone());
-
-            memo.insert((idx, remaining_runs), res);
-            res
+        arrangement
+    }
+
+    /// the nth of the ways to fill in the row, counting working gears before broken ones from the left
+    pub fn nth(&self, n: usize) -> Option<String> {
+        let (ways, width) = self.completions();
+        (n < ways[0][0]).then(|| self.walk(&ways, width, n))
+    }
+
+    /// up to `limit` of the ways to fill in the row, in the same order as `nth`
+    pub fn arrangements(&self, limit: usize) -> Vec<String> {
+        let (ways, width) = self.completions();
+        (0..ways[0][0].min(limit))
+            .map(|n| self.walk(&ways, width, n))
+            .collect()
+    }
+
+    /// one of the ways to fill in the row, picked by a random number. every way is equally likely
+    pub fn sample(&self, random: usize) -> Option<String> {
+        let (ways, width) = self.completions();
+        match ways[0][0] {
+            0 => None,
+            c => Some(self.walk(&ways, width, random % c)),
         }
     }
 }
 
-impl SolutionLinear<Vec<(Vec<Gear>, Vec<usize>)>, usize, usize> for Day12Solution {
-    fn load(input: &str) -> Result<Vec<(Vec<Gear>, Vec<usize>)>> {
-        Ok(input
+/// Counts the arrangements of rows, reusing the same buffers for each one
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    current: Vec<usize>,
+    next: Vec<usize>,
+}
+
+impl Counter {
+    pub fn count(&mut self, row: &Row) -> usize {
+        let runs = &row.runs;
+        let width = runs.iter().max().unwrap_or(&0) + 1;
+        let states = (runs.len() + 1) * width;
+        for buf in [&mut self.current, &mut self.next] {
+            buf.clear();
+            buf.resize(states, 0);
+        }
+        self.current[0] = 1;
+
+        for gear in &row.gears {
+            self.next.fill(0);
+            for i in 0..states {
+                let ways = self.current[i];
+                if ways == 0 {
+                    continue;
+                }
+                for broken in gear.options() {
+                    if let Some((g, r)) = advance(runs, (i / width, i % width), *broken) {
+                        self.next[g * width + r] += ways;
+                    }
+                }
+            }
+            std::mem::swap(&mut self.current, &mut self.next);
+        }
+
+        (0..states)
+            .filter(|i| is_done(runs, (i / width, i % width)))
+            .map(|i| self.current[i])
+            .sum()
+    }
+}
+
+/// the total arrangements of every row, unfolded by `factor`
+pub fn total(rows: &[Row], factor: usize) -> usize {
+    let mut counter = Counter::default();
+    rows.iter().map(|r| counter.count(&r.unfold(factor))).sum()
+}
+
+impl SolutionLinear<Vec<Row>, usize, usize> for Day12Solution {
+    fn load(input: &str) -> Result<Vec<Row>> {
+        input
             .lines()
-            .map(|l| {
-                let (gears, nums) = l.split_once(' ').unwrap();
-                (
-                    gears
-                        .chars()
-                        .map(|c| match c {
-                            '.' => Gear::Operational,
-                            '#' => Gear::Broken,
-                            _ => Gear::Unknown,
-                        })
-                        .collect_vec(),
-                    nums.split(',').map(|v| v.parse().unwrap()).collect_vec(),
-                )
-            })
-            .collect_vec())
+            .filter(|l| !l.trim().is_empty())
+            .map(Row::parse)
+            .collect()
     }
 
-    fn part1(input: &mut Vec<(Vec<Gear>, Vec<usize>)>) -> Result<usize> {
-        Ok(input
-            .iter()
-            .map(|(gears, nums)| {
-                let mut memo = HashMap::new();
-                recursive_solve(gears, &mut memo, 0, nums.to_vec())
-            })
-            .sum())
-    }
-
-    fn part2(input: &mut Vec<(Vec<Gear>, Vec<usize>)>, _part_1_solution: usize) -> Result<usize> {
-        Ok(input
-            .iter()
-            .map(|(gears, nums)| {
-                let mut memo = HashMap::new();
-                #[allow(unstable_name_collisions)]
-                let unfolded_gears = repeat_n(gears, 5)
-                    .intersperse(&vec![Gear::Unknown])
-                    .flatten()
-                    .copied()
-                    .collect_vec();
-                let unfolded_nums = nums.repeat(5);
-                recursive_solve(&unfolded_gears, &mut memo, 0, unfolded_nums)
-            })
-            .sum())
+    fn part1(input: &mut Vec<Row>) -> Result<usize> {
+        Ok(total(input, 1))
+    }
+
+    fn part2(input: &mut Vec<Row>, _part_1_solution: usize) -> Result<usize> {
+        Ok(total(input, UNFOLD_FACTOR))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day12Solution;
+    use super::{Day12Solution, Gear, Row};
     use crate::utils::solver_types::SolutionLinear;
+    use itertools::Itertools;
     use rstest::rstest;
 
+    /// every way of filling in the unknown gears that gives the right runs, by trying them all
+    fn brute_force(row: &Row) -> Vec<String> {
+        let unknown = row.gears.iter().filter(|g| **g == Gear::Unknown).count();
+        (0..1 << unknown)
+            .map(|bits: usize| {
+                let mut next = 0;
+                row.gears
+                    .iter()
+                    .map(|g| match g {
+                        Gear::Operational => '.',
+                        Gear::Broken => '#',
+                        Gear::Unknown => {
+                            next += 1;
+                            if bits & (1 << (next - 1)) != 0 {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .filter(|a| {
+                a.split('.')
+                    .filter(|r| !r.is_empty())
+                    .map(str::len)
+                    .eq(row.runs.iter().copied())
+            })
+            .sorted()
+            .collect()
+    }
+
     #[rstest]
     #[case(
         "???.### 1,1,3
@@ -189,4 +321,63 @@ mod tests {
         let p2 = Day12Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[rstest]
+    #[case("???.### 1,1,3", 1)]
+    #[case(".??..??...?##. 1,1,3", 4)]
+    #[case("?#?#?#?#?#?#?#? 1,3,1,6", 1)]
+    #[case("????.#...#... 4,1,1", 1)]
+    #[case("????.######..#####. 1,6,5", 4)]
+    #[case("?###???????? 3,2,1", 10)]
+    #[case("??????????? 1,1,2", 56)]
+    #[case("?? 3", 0)]
+    #[case("#.# 1", 0)]
+    #[case("..?.. 1", 1)]
+    fn validate_against_brute_force(#[case] line: &str, #[case] expected: usize) {
+        let row = Row::parse(line).unwrap();
+        let brute_force = brute_force(&row);
+
+        assert_eq!(expected, brute_force.len());
+        assert_eq!(expected, row.count());
+        // every arrangement, each exactly once
+        assert_eq!(
+            brute_force,
+            row.arrangements(usize::MAX)
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn validate_arrangements() {
+        let row = Row::parse(".??..??...?##. 1,1,3").unwrap();
+
+        // working gears are tried before broken ones, from the left
+        assert_eq!(
+            vec![
+                "..#...#...###.",
+                "..#..#....###.",
+                ".#....#...###.",
+                ".#...#....###."
+            ],
+            row.arrangements(10)
+        );
+        assert_eq!(2, row.arrangements(2).len());
+        assert_eq!(row.nth(0), row.sample(4));
+        assert_eq!(None, row.nth(4));
+        assert_eq!(None, Row::parse("#.# 1").unwrap().sample(0));
+    }
+
+    #[rstest]
+    #[case("???.### 1,1,3", [1, 1, 1, 1])]
+    #[case(".??..??...?##. 1,1,3", [4, 32, 256, 2048])]
+    #[case("?###???????? 3,2,1", [10, 150, 2250, 33750])]
+    fn validate_unfold(#[case] line: &str, #[case] expected: [usize; 4]) {
+        let row = Row::parse(line).unwrap();
+
+        assert_eq!(expected, [1, 2, 3, 4].map(|f| row.unfold(f).count()));
+        // small enough to check by brute force
+        assert_eq!(expected[1], brute_force(&row.unfold(2)).len());
+    }
 }
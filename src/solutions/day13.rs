@@ -1,106 +1,184 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
+use crate::utils::{
+    bitgrid::BitGrid,
+    point::Pt,
+    solver_types::{solve_linear, SolutionLinear},
+};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
-// TODO
 pub struct Day13Solution {}
 
 pub fn day13(input: &str) -> Result<f32> {
     solve_linear::<Day13Solution, _, _, _>(input)
 }
 
-type Mirror = Vec<Vec<bool>>;
+type Mirror = BitGrid;
 
-// stolen from https://stackoverflow.com/a/64499219/22029215
-fn transpose(mirror: Mirror) -> Mirror {
-    let len = mirror[0].len();
-    let mut iters: Vec<_> = mirror.into_iter().map(|n| n.into_iter()).collect();
-    (0..len)
-        .map(|_| iters.iter_mut().map(|n| n.next().unwrap()).collect_vec())
-        .collect_vec()
+/// Which way a mirror line runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// between two rows
+    Horizontal,
+    /// between two columns
+    Vertical,
 }
 
-// measure likeness between 0:1.., ..1:2.. etc. return left side or none if no reflection found
-fn measure_reflection(mirror: &Mirror) -> Option<usize> {
-    for top in 0..mirror.len() - 1 {
-        if (0..top + 1)
-            .rev()
-            .zip(top + 1..mirror.len())
-            .all(|(a, b)| mirror[a] == mirror[b])
-        {
-            return Some(top + 1);
+/// A line the pattern reflects across, once its smudges are cleaned up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    /// the number of rows above the line, or columns to the left of it
+    pub before: usize,
+    /// each pair of points that should be mirror images but aren't. flipping either one fixes it
+    pub smudges: Vec<(Pt<2>, Pt<2>)>,
+}
+
+#[allow(dead_code)]
+impl Reflection {
+    /// the puzzle's score: the columns to the left, or 100 times the rows above
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => self.before * 100,
+            Axis::Vertical => self.before,
         }
     }
-    None
 }
 
-// take the first instance where exactly one difference stops a reflection from being valid
-fn smudged_reflection(mirror: &Mirror) -> Option<usize> {
-    for top in 0..mirror.len() - 1 {
-        let diffs: usize = (0..top + 1)
-            .rev()
-            .zip(top + 1..mirror.len())
-            .map(|(a, b)| {
-                mirror[a]
-                    .iter()
-                    .zip(mirror[b].clone())
-                    .filter(|(aa, bb)| **aa != *bb)
-                    .count()
-            })
-            .sum();
-        if diffs == 1 {
-            return Some(top + 1);
+/// every line the pattern reflects across with exactly `smudges` pairs of points that don't match, rows first
+pub fn reflections(mirror: &Mirror, smudges: usize) -> Vec<Reflection> {
+    let (w, h) = (mirror.width(), mirror.height());
+    let pt = |x: usize, y: usize| Pt([x as isize, y as isize]);
+    let mut found = vec![];
+
+    for (axis, len) in [(Axis::Horizontal, h), (Axis::Vertical, w)] {
+        let diff = |a, b| match axis {
+            Axis::Horizontal => mirror.row_diff(a, b),
+            Axis::Vertical => mirror.col_diff(a, b),
+        };
+        for before in 1..len {
+            // the pairs of rows or columns either side of the line, out to the nearest edge
+            let pairs = (0..before).rev().zip(before..len);
+            let mut total = 0;
+            for (a, b) in pairs.clone() {
+                total += diff(a, b);
+                if total > smudges {
+                    break;
+                }
+            }
+            if total != smudges {
+                continue;
+            }
+
+            let smudges = pairs
+                .flat_map(|(a, b)| match axis {
+                    Axis::Horizontal => (0..w)
+                        .filter(|x| mirror.get(*x, a) != mirror.get(*x, b))
+                        .map(|x| (pt(x, a), pt(x, b)))
+                        .collect_vec(),
+                    Axis::Vertical => (0..h)
+                        .filter(|y| mirror.get(a, *y) != mirror.get(b, *y))
+                        .map(|y| (pt(a, y), pt(b, y)))
+                        .collect_vec(),
+                })
+                .collect();
+            found.push(Reflection {
+                axis,
+                before,
+                smudges,
+            });
         }
     }
-    None
+    found
+}
+
+/// draw the pattern with the mirror line on it, as `-` between rows or `|` between columns. smudges are drawn as `*`
+#[allow(dead_code)]
+pub fn show(mirror: &Mirror, reflection: &Reflection) -> String {
+    let smudged = |x: usize, y: usize| {
+        let p = Pt([x as isize, y as isize]);
+        reflection.smudges.iter().any(|(a, b)| *a == p || *b == p)
+    };
+    let mut res = String::new();
+    for y in 0..mirror.height() {
+        if reflection.axis == Axis::Horizontal && y == reflection.before {
+            res += &"-".repeat(mirror.width());
+            res.push('\n');
+        }
+        for x in 0..mirror.width() {
+            if reflection.axis == Axis::Vertical && x == reflection.before {
+                res.push('|');
+            }
+            res.push(match (smudged(x, y), mirror.get(x, y)) {
+                (true, _) => '*',
+                (_, true) => '#',
+                _ => '.',
+            });
+        }
+        res.push('\n');
+    }
+    res
+}
+
+/// add up the first reflection of each pattern, with this many smudges
+fn summarise(mirrors: &[Mirror], smudges: usize) -> Result<usize> {
+    mirrors
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            reflections(m, smudges)
+                .first()
+                .map(Reflection::summary)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "pattern {} has no reflection with {} smudges",
+                        i + 1,
+                        smudges
+                    )
+                })
+        })
+        .sum()
 }
 
 impl SolutionLinear<Vec<Mirror>, usize, usize> for Day13Solution {
     fn load(input: &str) -> Result<Vec<Mirror>> {
         Ok(input
             .split("\n\n")
-            .map(|square| {
-                square
-                    .lines()
-                    .map(|l| l.chars().map(|c| c == '#').collect_vec())
-                    .collect_vec()
-            })
+            .filter(|square| !square.trim().is_empty())
+            .map(|square| BitGrid::parse(square, '#'))
             .collect_vec())
     }
 
     fn part1(input: &mut Vec<Mirror>) -> Result<usize> {
-        Ok(input
-            .iter()
-            .map(|m| {
-                if let Some(v) = measure_reflection(m) {
-                    v * 100
-                } else {
-                    measure_reflection(&transpose(m.to_vec())).unwrap()
-                }
-            })
-            .sum())
+        summarise(input, 0)
     }
 
     fn part2(input: &mut Vec<Mirror>, _part_1_solution: usize) -> Result<usize> {
-        Ok(input
-            .iter()
-            .map(|m| {
-                if let Some(v) = smudged_reflection(m) {
-                    v * 100
-                } else {
-                    smudged_reflection(&transpose(m.to_vec())).unwrap()
-                }
-            })
-            .sum())
+        summarise(input, 1)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day13Solution;
-    use crate::utils::solver_types::SolutionLinear;
+    use super::{reflections, show, Axis, Day13Solution, Reflection};
+    use crate::utils::{bitgrid::BitGrid, point::Pt, solver_types::SolutionLinear};
     use rstest::rstest;
 
+    const FIRST: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.";
+    const SECOND: &str = "#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#
+";
+
     #[rstest]
     #[case(
         "#.##..##.
@@ -131,4 +209,68 @@ mod tests {
         let p2 = Day13Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    /// a pair of mismatched points, as (x, y)s
+    type Smudge = ((isize, isize), (isize, isize));
+
+    #[rstest]
+    #[case(FIRST, 0, vec![(Axis::Vertical, 5, vec![])])]
+    #[case(SECOND, 0, vec![(Axis::Horizontal, 4, vec![])])]
+    #[case(FIRST, 1, vec![(Axis::Horizontal, 3, vec![((0, 0), (0, 5))])])]
+    #[case(SECOND, 1, vec![(Axis::Horizontal, 1, vec![((4, 0), (4, 1))])])]
+    // every line works for a plain block
+    #[case("##\n##", 0, vec![(Axis::Horizontal, 1, vec![]), (Axis::Vertical, 1, vec![])])]
+    #[case("#.\n..", 1, vec![(Axis::Horizontal, 1, vec![((0, 0), (0, 1))]), (Axis::Vertical, 1, vec![((0, 0), (1, 0))])])]
+    #[case("#.\n..", 2, vec![])]
+    fn validate_reflections(
+        #[case] pattern: &str,
+        #[case] smudges: usize,
+        #[case] expected: Vec<(Axis, usize, Vec<Smudge>)>,
+    ) {
+        let expected = expected
+            .into_iter()
+            .map(|(axis, before, smudges)| Reflection {
+                axis,
+                before,
+                smudges: smudges
+                    .into_iter()
+                    .map(|((ax, ay), (bx, by))| (Pt([ax, ay]), Pt([bx, by])))
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            expected,
+            reflections(&BitGrid::parse(pattern, '#'), smudges)
+        );
+    }
+
+    #[test]
+    fn validate_show() {
+        let mirror = BitGrid::parse(FIRST, '#');
+
+        assert_eq!(
+            "#.##.|.##.
+..#.#|#.#.
+##...|...#
+##...|...#
+..#.#|#.#.
+..##.|.##.
+#.#.#|#.#.
+",
+            show(&mirror, &reflections(&mirror, 0)[0])
+        );
+        assert_eq!(
+            "*.##..##.
+..#.##.#.
+##......#
+---------
+##......#
+..#.##.#.
+*.##..##.
+#.#.##.#.
+",
+            show(&mirror, &reflections(&mirror, 1)[0])
+        );
+    }
 }
@@ -1,153 +1,322 @@
-use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
 use crate::utils::{
-    point::Pt,
+    bitgrid::BitGrid,
+    cycle::{detect, History},
     solver_types::{solve_linear, SolutionLinear},
 };
-use anyhow::Result;
-use indexmap::IndexMap;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day14Solution {}
 
-#[derive(Clone)]
-struct Dish {
-    cubes: HashSet<Pt<2>>,
-    rocks: HashSet<Pt<2>>,
-    max_x: isize,
-    max_y: isize,
-    orders: HashMap<Pt<2>, Vec<Pt<2>>>,
-}
-
 pub fn day14(input: &str) -> Result<f32> {
     solve_linear::<Day14Solution, _, _, _>(input)
 }
 
-impl Dish {
-    fn roll_rocks(&mut self, direction: Pt<2>) {
-        let order = self.orders.get(&direction).unwrap();
+/// part 2 spins the dish this many times
+const SPINS: usize = 1_000_000_000;
 
-        order.iter().for_each(|pt| {
-            if !self.rocks.contains(pt) {
-                return;
-            }
-            self.rocks.remove(pt);
-            let mut new_location = *pt;
-            let mut next = new_location + direction;
-
-            while !self.cubes.contains(&next)
-                && !self.rocks.contains(&next)
-                && next.0[0] != self.max_x
-                && next.0[0] >= 0
-                && next.0[1] != self.max_y
-                && next.0[1] >= 0
-            {
-                new_location = next;
-                next = new_location + direction;
-            }
-            self.rocks.insert(new_location);
-        });
+/// A way to tilt the dish, named after the edge the rocks roll towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+/// part 2's spin cycle
+pub const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
+
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match c.to_ascii_uppercase() {
+            'N' => Ok(Direction::North),
+            'W' => Ok(Direction::West),
+            'S' => Ok(Direction::South),
+            'E' => Ok(Direction::East),
+            _ => Err(anyhow!("'{}' isn't a direction - use N, W, S or E", c)),
+        }
     }
+}
 
-    fn strain(&self) -> isize {
-        self.rocks.iter().map(|Pt([_, iy])| self.max_y - iy).sum()
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Direction::North => 'N',
+            Direction::West => 'W',
+            Direction::South => 'S',
+            Direction::East => 'E',
+        };
+        write!(f, "{}", c)
     }
 }
 
-impl SolutionLinear<Dish, isize, isize> for Day14Solution {
-    fn load(input: &str) -> Result<Dish> {
-        let mut x = 0;
-        let mut y = 0;
-        let mut rocks = HashSet::new();
-        let mut cubes = HashSet::new();
-
-        for l in input.lines() {
-            x = 0;
-            for c in l.chars() {
-                match c {
-                    'O' => {
-                        rocks.insert(Pt([x, y]));
-                    }
-                    '#' => {
-                        cubes.insert(Pt([x, y]));
-                    }
-                    _ => (),
-                }
-                x += 1;
-            }
-            y += 1;
+#[allow(dead_code)]
+impl Direction {
+    /// a sequence of tilts, like `NWSE`
+    pub fn parse_sequence(s: &str) -> Result<Vec<Self>> {
+        let tilts = s
+            .trim()
+            .chars()
+            .map(Self::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        if tilts.is_empty() {
+            return Err(anyhow!("there are no tilts in the sequence"));
         }
+        Ok(tilts)
+    }
+}
 
-        let n_order = (0..y)
-            .flat_map(|y| (0..x).map(move |x| Pt([x, y])))
-            .collect_vec();
+/// A stretch of a row or column between cube rocks, where round rocks can roll freely.
+/// covers `start..end` along row or column number `line`
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    line: usize,
+    start: usize,
+    end: usize,
+}
 
-        let w_order = (0..x)
-            .flat_map(|x| (0..y).map(move |y| Pt([x, y])))
-            .collect_vec();
+/// every stretch between cubes, in a grid that's `lines` rows or columns of `len` cells, where `is_cube(line, i)`
+fn segments(lines: usize, len: usize, is_cube: impl Fn(usize, usize) -> bool) -> Vec<Segment> {
+    (0..lines)
+        .flat_map(|line| {
+            (0..len)
+                .filter(|i| is_cube(line, *i))
+                .chain([len])
+                .scan(0, move |start, cube| {
+                    let s = Segment {
+                        line,
+                        start: *start,
+                        end: cube,
+                    };
+                    *start = cube + 1;
+                    Some(s)
+                })
+                .filter(|s| s.start < s.end)
+                .collect_vec()
+        })
+        .collect()
+}
 
-        // lol. lmao.
-        let orders = HashMap::from([
-            (Pt([0, -1]), n_order.clone()),
-            (Pt([0, 1]), n_order.into_iter().rev().collect_vec()),
-            (Pt([-1, 0]), w_order.clone()),
-            (Pt([1, 0]), w_order.into_iter().rev().collect_vec()),
-        ]);
+/// the total load of some rocks on one edge
+fn load(rocks: &BitGrid, edge: Direction) -> usize {
+    let (w, h) = (rocks.width(), rocks.height());
+    rocks
+        .points()
+        .map(|p| {
+            let (x, y) = (p.0[0] as usize, p.0[1] as usize);
+            match edge {
+                Direction::North => h - y,
+                Direction::South => y + 1,
+                Direction::West => w - x,
+                Direction::East => x + 1,
+            }
+        })
+        .sum()
+}
 
-        Ok(Dish {
+#[derive(Clone)]
+pub struct Dish {
+    cubes: BitGrid,
+    rocks: BitGrid,
+    /// the stretches of each column, and of each row
+    columns: Vec<Segment>,
+    rows: Vec<Segment>,
+}
+
+#[allow(dead_code)]
+impl Dish {
+    pub fn parse(input: &str) -> Result<Self> {
+        if let Some(c) = input.chars().find(|c| !".#O\n\r".contains(*c)) {
+            return Err(anyhow!("unexpected character '{}' in the dish", c));
+        }
+        let cubes = BitGrid::parse(input, '#');
+        let (w, h) = (cubes.width(), cubes.height());
+        Ok(Self {
+            columns: segments(w, h, |x, y| cubes.get(x, y)),
+            rows: segments(h, w, |y, x| cubes.get(x, y)),
+            rocks: BitGrid::parse(input, 'O'),
             cubes,
-            rocks,
-            max_x: x,
-            max_y: y,
-            orders,
         })
     }
 
-    fn part1(input: &mut Dish) -> Result<isize> {
-        let mut p1 = input.clone();
-        p1.roll_rocks(Pt([0, -1]));
+    /// roll every round rock in `rocks` as far as it goes, around this dish's cubes. each stretch between cubes just
+    /// has its rocks counted and packed in at the far end, rather than moving them one cell at a time
+    pub fn tilt(&self, rocks: &mut BitGrid, direction: Direction) {
+        let (segments, along_y) = match direction {
+            Direction::North | Direction::South => (&self.columns, true),
+            Direction::West | Direction::East => (&self.rows, false),
+        };
+        let towards_start = matches!(direction, Direction::North | Direction::West);
 
-        Ok(p1.strain())
+        for s in segments {
+            let cell = |i: usize| if along_y { (s.line, i) } else { (i, s.line) };
+            let mut count = 0;
+            for i in s.start..s.end {
+                let (x, y) = cell(i);
+                if rocks.get(x, y) {
+                    count += 1;
+                    rocks.set(x, y, false);
+                }
+            }
+            let packed = if towards_start {
+                s.start..s.start + count
+            } else {
+                s.end - count..s.end
+            };
+            for i in packed {
+                let (x, y) = cell(i);
+                rocks.set(x, y, true);
+            }
+        }
+    }
+
+    /// tilt the dish in each direction in turn
+    pub fn spin(&self, rocks: &mut BitGrid, tilts: &[Direction]) {
+        for d in tilts {
+            self.tilt(rocks, *d);
+        }
     }
 
-    // in short, at some point we enter a loop of possible states. we want to find when that loop starts, and its period
-    // then we can just modulo to find where in a loop the millionth cycle should be
-    fn part2(input: &mut Dish, _part_1_solution: isize) -> Result<isize> {
-        let ds: [Pt<2>; 4] = [Pt([0, -1]), Pt([-1, 0]), Pt([0, 1]), Pt([1, 0])];
-        let mut seen: IndexMap<Vec<Pt<2>>, (i32, isize)> = IndexMap::new();
-        let mut repeat_starts = 0;
-        let mut loop_size = 0;
+    /// spin until the rocks end up somewhere they've been before
+    pub fn history(&self, tilts: &[Direction]) -> History<BitGrid> {
+        detect(
+            self.rocks.clone(),
+            |rocks| {
+                let mut rocks = rocks.clone();
+                self.spin(&mut rocks, tilts);
+                rocks
+            },
+            BitGrid::clone,
+        )
+    }
 
-        for x in 0..9999 {
-            for d in ds {
-                input.roll_rocks(d);
-            }
+    /// the dish after `n` spins. the dish soon falls into a loop, so this skips ahead once it's found one
+    pub fn after_spins(&self, tilts: &[Direction], n: usize) -> Dish {
+        self.with_rocks(self.history(tilts).state_at(n).clone())
+    }
 
-            let mut key = input.rocks.clone().into_iter().collect_vec();
-            key.sort();
+    fn with_rocks(&self, rocks: BitGrid) -> Dish {
+        Dish {
+            rocks,
+            ..self.clone()
+        }
+    }
 
-            if let Some((v, _)) = seen.get(&key) {
-                repeat_starts = *v;
-                loop_size = x - repeat_starts;
-                break;
+    /// the total load on one edge: each rock counts for its distance from the opposite edge, including its own cell
+    pub fn load(&self, edge: Direction) -> usize {
+        load(&self.rocks, edge)
+    }
+
+    /// every spin in a `history` from the start until the dish repeats itself, drawn one after the other
+    pub fn frames(&self, history: &History<BitGrid>) -> String {
+        let mut res = String::new();
+        for (i, rocks) in history.states().iter().enumerate() {
+            res += &format!("after {} spins:\n{}\n", i, self.with_rocks(rocks.clone()));
+        }
+        res += &format!(
+            "spin {} is the same as spin {}, so it loops every {} spins",
+            history.repeat.offset + history.repeat.period,
+            history.repeat.offset,
+            history.repeat.period
+        );
+        res
+    }
+}
+
+impl Display for Dish {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.rocks.height() {
+            for x in 0..self.rocks.width() {
+                let c = match (self.rocks.get(x, y), self.cubes.get(x, y)) {
+                    (true, _) => 'O',
+                    (_, true) => '#',
+                    _ => '.',
+                };
+                write!(f, "{}", c)?;
             }
-            seen.insert(key, (x, input.strain()));
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// How to spin the dish, when it's run with options from the command line
+#[derive(Debug, Clone)]
+pub struct SpinOptions {
+    pub tilts: Vec<Direction>,
+    pub spins: usize,
+    /// the edge to measure the load on
+    pub edge: Direction,
+    /// print the dish after every spin, until it starts repeating
+    pub frames: bool,
+}
+
+impl Default for SpinOptions {
+    fn default() -> Self {
+        Self {
+            tilts: SPIN_CYCLE.to_vec(),
+            spins: SPINS,
+            edge: Direction::North,
+            frames: false,
         }
+    }
+}
+
+/// spin the dish in the input however the options say. gives the load on the chosen edge afterwards, and the frames
+/// if they were asked for
+pub fn spin_dish(input: &str, options: &SpinOptions) -> Result<(usize, Option<String>)> {
+    let dish = Dish::parse(input)?;
+    let history = dish.history(&options.tilts);
+    let frames = options.frames.then(|| dish.frames(&history));
+    Ok((load(history.state_at(options.spins), options.edge), frames))
+}
 
-        let last = (repeat_starts - 1) + (1_000_000_000 - repeat_starts) % (loop_size);
+impl SolutionLinear<Dish, usize, usize> for Day14Solution {
+    fn load(input: &str) -> Result<Dish> {
+        Dish::parse(input)
+    }
 
-        let sol = seen.iter().find(|(_, (idx, _))| *idx == last).unwrap().1 .1;
+    fn part1(input: &mut Dish) -> Result<usize> {
+        let mut rocks = input.rocks.clone();
+        input.tilt(&mut rocks, Direction::North);
+        Ok(load(&rocks, Direction::North))
+    }
 
-        Ok(sol)
+    // in short, at some point we enter a loop of possible states. find when that loop starts and its period,
+    // then we can skip straight to where in the loop the billionth cycle lands
+    fn part2(input: &mut Dish, _part_1_solution: usize) -> Result<usize> {
+        Ok(input.after_spins(&SPIN_CYCLE, SPINS).load(Direction::North))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day14Solution;
+    use super::{spin_dish, Day14Solution, Direction, Dish, SpinOptions, SPIN_CYCLE};
     use crate::utils::solver_types::SolutionLinear;
     use rstest::rstest;
 
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....
+";
+
     #[rstest]
     #[case(
         "O....#....
@@ -164,7 +333,7 @@ O.#..O.#.#
         136,
         64
     )]
-    fn validate_day14(#[case] input: &str, #[case] expected_1: isize, #[case] expected_2: isize) {
+    fn validate_day14(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
         let mut input = Day14Solution::load(input).unwrap();
 
         let p1 = Day14Solution::part1(&mut input).unwrap();
@@ -173,4 +342,157 @@ O.#..O.#.#
         let p2 = Day14Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    /// roll each rock one cell at a time until it hits something, the slow way
+    fn naive_tilt(dish: &str, direction: Direction) -> String {
+        let mut grid: Vec<Vec<char>> = dish.lines().map(|l| l.chars().collect()).collect();
+        let (dx, dy) = match direction {
+            Direction::North => (0, -1),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::East => (1, 0),
+        };
+        let (w, h) = (grid[0].len() as isize, grid.len() as isize);
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for y in 0..h {
+                for x in 0..w {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if grid[y as usize][x as usize] == 'O'
+                        && (0..w).contains(&nx)
+                        && (0..h).contains(&ny)
+                        && grid[ny as usize][nx as usize] == '.'
+                    {
+                        grid[ny as usize][nx as usize] = 'O';
+                        grid[y as usize][x as usize] = '.';
+                        moved = true;
+                    }
+                }
+            }
+        }
+        grid.iter()
+            .map(|r| r.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[rstest]
+    #[case(
+        1,
+        ".....#....
+....#...O#
+...OO##...
+.OO#......
+.....OOO#.
+.O#...O#.#
+....O#....
+......OOOO
+#...O###..
+#..OO#....
+"
+    )]
+    #[case(
+        3,
+        ".....#....
+....#...O#
+.....##...
+..O#......
+.....OOO#.
+.O#...O#.#
+....O#...O
+.......OOO
+#...O###.O
+#.OOO#...O
+"
+    )]
+    fn validate_spins(#[case] spins: usize, #[case] expected: &str) {
+        let dish = Dish::parse(EXAMPLE).unwrap();
+
+        assert_eq!(expected, dish.after_spins(&SPIN_CYCLE, spins).to_string());
+    }
+
+    #[rstest]
+    #[case("NWSE")]
+    #[case("NE")]
+    #[case("SSW")]
+    #[case("E")]
+    fn validate_against_naive(#[case] tilts: &str) {
+        let tilts = Direction::parse_sequence(tilts).unwrap();
+        let dish = Dish::parse(EXAMPLE).unwrap();
+
+        let mut naive = EXAMPLE.to_string();
+        for n in 0..20 {
+            assert_eq!(
+                naive,
+                dish.after_spins(&tilts, n).to_string(),
+                "after {} spins",
+                n
+            );
+            naive = tilts.iter().fold(naive, |d, t| naive_tilt(&d, *t));
+        }
+    }
+
+    #[rstest]
+    #[case(Direction::North, 104)]
+    #[case(Direction::South, 94)]
+    #[case(Direction::West, 121)]
+    #[case(Direction::East, 77)]
+    fn validate_load(#[case] edge: Direction, #[case] expected: usize) {
+        let dish = Dish::parse(EXAMPLE).unwrap();
+        let brute_force: usize = EXAMPLE
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == 'O')
+                    .map(move |(x, _)| (x, y))
+            })
+            .map(|(x, y)| match edge {
+                Direction::North => 10 - y,
+                Direction::South => y + 1,
+                Direction::West => 10 - x,
+                Direction::East => x + 1,
+            })
+            .sum();
+
+        assert_eq!(brute_force, dish.load(edge));
+        assert_eq!(expected, dish.load(edge));
+    }
+
+    #[test]
+    fn validate_frames() {
+        let dish = Dish::parse(EXAMPLE).unwrap();
+        let frames = dish.frames(&dish.history(&SPIN_CYCLE));
+
+        assert!(frames.starts_with(&format!("after 0 spins:\n{}", EXAMPLE)));
+        assert_eq!(10, frames.matches("spins:").count());
+        assert!(frames.ends_with("spin 10 is the same as spin 3, so it loops every 7 spins"));
+    }
+
+    #[test]
+    fn validate_spin_dish() {
+        let options = SpinOptions::default();
+        assert_eq!((64, None), spin_dish(EXAMPLE, &options).unwrap());
+
+        let options = SpinOptions {
+            frames: true,
+            ..options
+        };
+        let dish = Dish::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            (64, Some(dish.frames(&dish.history(&SPIN_CYCLE)))),
+            spin_dish(EXAMPLE, &options).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("NX", "'X' isn't a direction - use N, W, S or E")]
+    #[case("", "there are no tilts in the sequence")]
+    fn validate_bad_tilts(#[case] tilts: &str, #[case] expected: &str) {
+        assert_eq!(
+            expected,
+            Direction::parse_sequence(tilts).unwrap_err().to_string()
+        );
+    }
 }
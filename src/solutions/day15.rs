@@ -36,7 +36,7 @@ impl SolutionLinear<Vec<Vec<char>>, usize, usize> for Day15Solution {
                 } else {
                     let v = hash(s) as usize;
                     note.insert(s, v);
-                    v as usize
+                    v
                 }
             })
             .sum())
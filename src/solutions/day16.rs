@@ -33,29 +33,15 @@ struct Beam {
 impl Space {
     fn enter(&self, beam: &Beam) -> Vec<Beam> {
         let pos = beam.pos + beam.dir.val();
-        match self {
-            Space::Empty => vec![Beam { pos, dir: beam.dir }],
-            Space::ForwardSlash => match beam.dir {
-                D::Up => vec![Beam { pos, dir: D::Right }],
-                D::Down => vec![Beam { pos, dir: D::Left }],
-                D::Left => vec![Beam { pos, dir: D::Down }],
-                D::Right => vec![Beam { pos, dir: D::Up }],
-            },
-            Space::BackSlash => match beam.dir {
-                D::Up => vec![Beam { pos, dir: D::Left }],
-                D::Down => vec![Beam { pos, dir: D::Right }],
-                D::Left => vec![Beam { pos, dir: D::Up }],
-                D::Right => vec![Beam { pos, dir: D::Down }],
-            },
-            Space::Minus => match beam.dir {
-                D::Up | D::Down => vec![Beam { pos, dir: D::Left }, Beam { pos, dir: D::Right }],
-                D::Left | D::Right => vec![Beam { pos, dir: beam.dir }],
-            },
-            Space::Pipe => match beam.dir {
-                D::Up | D::Down => vec![Beam { pos, dir: beam.dir }],
-                D::Left | D::Right => vec![Beam { pos, dir: D::Up }, Beam { pos, dir: D::Down }],
-            },
-        }
+        let dirs = match (self, beam.dir.is_vertical()) {
+            (Space::Empty, _) | (Space::Minus, false) | (Space::Pipe, true) => vec![beam.dir],
+            (Space::ForwardSlash, true) | (Space::BackSlash, false) => vec![beam.dir.turn_right()],
+            (Space::ForwardSlash, false) | (Space::BackSlash, true) => vec![beam.dir.turn_left()],
+            (Space::Minus, true) | (Space::Pipe, false) => {
+                vec![beam.dir.turn_left(), beam.dir.turn_right()]
+            }
+        };
+        dirs.into_iter().map(|dir| Beam { pos, dir }).collect()
     }
 }
 
@@ -107,7 +93,7 @@ impl SolutionLinear<Grid<Space, 2>, usize, usize> for Day16Solution {
     fn part2(input: &mut Grid<Space, 2>, _part_1_solution: usize) -> Result<usize> {
         let (_, [max_x, max_y]) = input.bounds();
 
-        let binding = vec![
+        let binding = [
             (0..max_x + 1)
                 .map(|x| Beam {
                     pos: Pt([x, -1]),
@@ -1,5 +1,5 @@
 use crate::utils::{
-    grid::Grid,
+    geometry::{boundary_points, interior_points, trace},
     load_input::load_lines,
     point::{Pt, D},
     solver_types::{solve_linear, SolutionLinear},
@@ -32,58 +32,16 @@ fn to_hex(s: &str) -> (D, usize) {
     )
 }
 
-fn carve(commands: &Vec<Command>) -> Grid<isize, 2> {
-    let mut ptr = Pt([0, 0]);
-    let mut grid = Grid::from(vec![(ptr, 1)]);
-    let mut distance = 2isize;
-
-    for &(dir, steps, _) in commands {
-        for _ in 1..steps + 1 {
-            ptr += dir.val();
-
-            if !grid.grid.contains_key(&ptr) {
-                grid.grid.insert(ptr, distance);
-            }
-
-            distance += 1;
-        }
-    }
-    grid
-}
-
-// unwind method again
-fn fill(grid: &mut Grid<isize, 2>) {
-    let ([min_x, min_y], [max_x, max_y]) = grid.bounds();
-    let mut inside = 0;
-    let size = *grid.grid.iter().last().unwrap().1;
-
-    for y in min_y..max_y + 1 {
-        for x in min_x..max_x + 1 {
-            let pt = Pt([x, y]);
-            let below = pt + D::Down.val();
-            let diff = grid
-                .grid
-                .get(&pt)
-                .and_then(|d1| grid.grid.get(&below).map(|d2| d1 - d2 % size));
-
-            if x == max_x {
-                inside = 0;
-            } else {
-                match diff {
-                    Some(v) => {
-                        if v.abs() == 1 {
-                            inside += v
-                        }
-                    }
-                    _ => {
-                        if inside != 0 {
-                            grid.grid.insert(pt, -1);
-                        }
-                    }
-                }
-            }
-        }
-    }
+/// the trench plus everything it encloses. the corners are all we need: the shoelace formula gives the area,
+/// then Pick's theorem turns that into the number of cells inside the trench
+fn lagoon_size(commands: &[Command]) -> usize {
+    let corners = trace(
+        Pt([0, 0]),
+        commands
+            .iter()
+            .map(|(dir, steps, _)| dir.val() * *steps as isize),
+    );
+    (boundary_points(&corners) + interior_points(&corners)) as usize
 }
 
 fn fix_commands(commands: Vec<Command>) -> Vec<Command> {
@@ -99,13 +57,7 @@ impl SolutionLinear<Vec<Command>, usize, usize> for Day18Solution {
             let mut parts = l.split_whitespace();
 
             (
-                match parts.next().unwrap() {
-                    "R" => D::Right,
-                    "L" => D::Left,
-                    "U" => D::Up,
-                    "D" => D::Down,
-                    _ => panic!("bad letter, not nice :("),
-                },
+                parts.next().unwrap().parse().unwrap(),
                 parts.next().unwrap().parse().unwrap(),
                 to_hex(parts.next().unwrap()),
             )
@@ -113,16 +65,11 @@ impl SolutionLinear<Vec<Command>, usize, usize> for Day18Solution {
     }
 
     fn part1(input: &mut Vec<Command>) -> Result<usize> {
-        let mut g = carve(input);
-        fill(&mut g);
-        Ok(g.grid.len())
+        Ok(lagoon_size(input))
     }
 
     fn part2(input: &mut Vec<Command>, _part_1_solution: usize) -> Result<usize> {
-        let input = fix_commands(input.to_vec());
-        let mut g = carve(&input);
-        fill(&mut g);
-        Ok(g.grid.len())
+        Ok(lagoon_size(&fix_commands(input.to_vec())))
     }
 }
 
@@ -133,7 +80,6 @@ mod tests {
     use rstest::rstest;
 
     #[rstest]
-    #[ignore = "delete to test solution"]
     #[case(
         "R 6 (#70c710)
 D 5 (#0dc571)
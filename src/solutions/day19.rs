@@ -1,181 +1,44 @@
-use std::{cmp::Ordering, collections::HashMap};
+use crate::utils::{
+    solver_types::{solve_linear, SolutionLinear},
+    workflow::{parse_part, Part, Workflows},
+};
+use anyhow::{anyhow, Result};
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
-use itertools::Itertools;
-
-// TODO
 pub struct Day19Solution {}
 
 pub fn day19(input: &str) -> Result<f32> {
     solve_linear::<Day19Solution, _, _, _>(input)
 }
 
-type Ranges = [(usize, usize); 4];
-type Obj = [usize; 4];
-#[derive(Debug)]
-enum Action {
-    To(String),
-    Reject,
-    Accept,
-}
-#[derive(Debug)]
-enum Instr {
-    Cmp(usize, Ordering, usize, Action),
-    Just(Action),
-}
-
-const IDX: [char; 4] = ['x', 'm', 'a', 's'];
-
-fn analyze(instrs: &HashMap<String, Vec<Instr>>, obj: Obj) -> Option<Obj> {
-    let mut i = "in".to_string();
-
-    loop {
-        for ii in instrs.get(&i).unwrap() {
-            match ii {
-                Instr::Cmp(idx, ord, val, a) => {
-                    let res = match ord {
-                        Ordering::Less => obj[*idx] < *val,
-                        Ordering::Greater => obj[*idx] > *val,
-                        Ordering::Equal => panic!("unexpected comp"),
-                    };
-                    if res {
-                        match a {
-                            Action::To(new_i) => {
-                                i = new_i.clone();
-                                break;
-                            }
-                            Action::Reject => return None,
-                            Action::Accept => return Some(obj),
-                        }
-                    }
-                }
-                Instr::Just(a) => match a {
-                    Action::To(new_i) => {
-                        i = new_i.clone();
-                        break;
-                    }
-                    Action::Reject => return None,
-                    Action::Accept => return Some(obj),
-                },
-            }
-        }
-    }
-}
-
-// left = matches, right = doesnt match
-fn split_range(ranges: Ranges, idx: usize, val: usize, cmp: Ordering) -> (Ranges, Ranges) {
-    let mut left = ranges;
-    let mut right = ranges;
-
-    match cmp {
-        Ordering::Less => {
-            left[idx] = (left[idx].0, val - 1);
-            right[idx] = (val, right[idx].1);
-        }
-        Ordering::Greater => {
-            left[idx] = (val + 1, left[idx].1);
-            right[idx] = (right[idx].0, val);
-        }
-        Ordering::Equal => panic!("unexpected operand"),
-    }
-
-    (left, right)
-}
-
-fn sum_items(ranges: Ranges) -> usize {
-    ranges.iter().map(|(a, b)| b - a + 1).product()
-}
-
-fn enumerate(instrs: &HashMap<String, Vec<Instr>>, i: String, idx: usize, ranges: Ranges) -> usize {
-    match instrs.get(&i).unwrap().get(idx).unwrap() {
-        Instr::Just(a) => match a {
-            Action::Reject => 0,
-            Action::Accept => sum_items(ranges),
-            Action::To(new_i) => enumerate(instrs, new_i.to_string(), 0, ranges),
-        },
-        // we can always assume a cmp is not a terminator
-        Instr::Cmp(c_idx, ord, val, a) => {
-            let (left, right) = split_range(ranges, *c_idx, *val, *ord);
-            let left_result = match a {
-                Action::Reject => 0,
-                Action::Accept => sum_items(left),
-                Action::To(new_i) => enumerate(instrs, new_i.to_string(), 0, left),
-            };
-            let right_result = enumerate(instrs, i, idx + 1, right);
-
-            left_result + right_result
-        }
-    }
-}
-
-impl SolutionLinear<(HashMap<String, Vec<Instr>>, Vec<Obj>), usize, usize> for Day19Solution {
-    fn load(input: &str) -> Result<(HashMap<String, Vec<Instr>>, Vec<Obj>)> {
-        let (instrs, objs) = input.split_once("\n\n").unwrap();
-
-        let parsed_instrs = instrs
-            .lines()
-            .map(|l| {
-                let (tag, steps) = l.split_once('{').unwrap();
-                let parsed_steps = steps[0..steps.len() - 1]
-                    .split(',')
-                    .map(|st| match st.split_once(':') {
-                        Some((ab, c)) => {
-                            let mut chars = ab.chars();
-                            let x = chars.next().unwrap();
-                            let cmp = match chars.next().unwrap() {
-                                '>' => Ordering::Greater,
-                                '<' => Ordering::Less,
-                                _ => panic!("unexpected character"),
-                            };
-                            let val =
-                                chars.fold(0, |acc, v| acc * 10 + v.to_digit(10).unwrap()) as usize;
-                            let i = match c {
-                                "R" => Action::Reject,
-                                "A" => Action::Accept,
-                                _ => Action::To(c.to_string()),
-                            };
-                            Instr::Cmp(IDX.iter().position(|i| i == &x).unwrap(), cmp, val, i)
-                        }
-                        None => match st {
-                            "R" => Instr::Just(Action::Reject),
-                            "A" => Instr::Just(Action::Accept),
-                            _ => Instr::Just(Action::To(st.to_string())),
-                        },
-                    })
-                    .collect_vec();
-                (tag.to_string(), parsed_steps)
-            })
-            .collect();
-
-        let parsed_objs = objs
-            .lines()
-            .map(|l| {
-                l[1..l.len() - 1]
-                    .split(',')
-                    .map(|v| v[2..].parse().unwrap())
-                    .collect_vec()
-                    .try_into()
-                    .unwrap()
-            })
-            .collect_vec();
-
-        Ok((parsed_instrs, parsed_objs))
+impl SolutionLinear<(Workflows, Vec<Part>), usize, usize> for Day19Solution {
+    fn load(input: &str) -> Result<(Workflows, Vec<Part>)> {
+        let (workflows, parts) = input
+            .split_once("\n\n")
+            .ok_or_else(|| anyhow!("expected workflows and parts separated by a blank line"))?;
+
+        Ok((
+            Workflows::parse(workflows)?,
+            parts
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(parse_part)
+                .collect::<Result<_>>()?,
+        ))
     }
 
-    fn part1((instrs, objs): &mut (HashMap<String, Vec<Instr>>, Vec<Obj>)) -> Result<usize> {
-        Ok(objs
+    fn part1((workflows, parts): &mut (Workflows, Vec<Part>)) -> Result<usize> {
+        Ok(parts
             .iter()
-            .filter_map(|o| analyze(instrs, *o))
-            .map(|o| o.iter().sum::<usize>())
+            .filter(|p| workflows.accepts(p))
+            .map(|p| p.iter().sum::<usize>())
             .sum())
     }
 
     fn part2(
-        (instrs, _): &mut (HashMap<String, Vec<Instr>>, Vec<Obj>),
+        (workflows, _): &mut (Workflows, Vec<Part>),
         _part_1_solution: usize,
     ) -> Result<usize> {
-        Ok(enumerate(instrs, "in".to_string(), 0, [(1, 4000); 4]))
+        Ok(workflows.count_accepted())
     }
 }
 
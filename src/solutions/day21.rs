@@ -1,8 +1,10 @@
 use crate::utils::{
+    load_input::{load_2d_grid_with, GridOptions},
+    maths::extrapolate,
     point::Pt,
     solver_types::{solve_linear, SolutionLinear},
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
 use itertools::Itertools;
 
@@ -12,10 +14,12 @@ pub fn day21(input: &str) -> Result<f32> {
     solve_linear::<Day21Solution, _, _, _>(input)
 }
 
+const PART_2_STEPS: usize = 26_501_365;
+
 const DIRS: [Pt<2>; 4] = [Pt([0, 1]), Pt([-1, 0]), Pt([1, 0]), Pt([0, -1])];
 
 fn steppin(pts: &IndexSet<Pt<2>>, start: Pt<2>, steps: usize) -> usize {
-    let Pt([max_x, max_y]) = pts.last().unwrap();
+    let size = *pts.last().unwrap() + Pt([1, 1]);
     (0..steps)
         .fold(vec![start], |acc, _| {
             acc.iter()
@@ -23,11 +27,7 @@ fn steppin(pts: &IndexSet<Pt<2>>, start: Pt<2>, steps: usize) -> usize {
                     DIRS.iter().filter_map(|d| {
                         let new_d = *p + *d;
                         // https://www.youtube.com/watch?v=kpk2tdsPh0A&t=638s
-                        let parallel = Pt([
-                            new_d.0[0].rem_euclid(max_x + 1),
-                            new_d.0[1].rem_euclid(max_y + 1),
-                        ]);
-                        if pts.contains(&parallel) {
+                        if pts.contains(&new_d.rem_euclid(size)) {
                             Some(new_d)
                         } else {
                             None
@@ -40,34 +40,23 @@ fn steppin(pts: &IndexSet<Pt<2>>, start: Pt<2>, steps: usize) -> usize {
         .len()
 }
 
-fn find_best_fit(x1: usize, y1: usize, x2: usize, y2: usize, c: usize) -> (usize, usize) {
-    for (a, b) in (10_000..30_000).cartesian_product(10_000..30_000) {
-        if (a * x1.pow(2) + b * x1 + c) == y1 && (a * x2.pow(2) + b * x2 + c) == y2 {
-            return (a, b);
-        }
-    }
-    panic!("terms not in range")
-}
-
 impl SolutionLinear<(IndexSet<Pt<2>>, Pt<2>), usize, usize> for Day21Solution {
     fn load(input: &str) -> Result<(IndexSet<Pt<2>>, Pt<2>)> {
-        let mut start = Pt([0, 0]);
-        let mut set = IndexSet::new();
-
-        for (y, l) in input.lines().enumerate() {
-            for (x, c) in l.chars().enumerate() {
-                match c {
-                    '#' => (),
-                    _ => {
-                        if c == 'S' {
-                            start = Pt([x as isize, y as isize]);
-                        }
-                        set.insert(Pt([x as isize, y as isize]));
-                    }
-                }
-            }
-        }
-        Ok((set, start))
+        let (garden, symbols) = load_2d_grid_with(
+            input,
+            |_| true,
+            &GridOptions {
+                ignore: "#",
+                symbols: "S",
+                ..Default::default()
+            },
+        );
+        let start = symbols
+            .get(&'S')
+            .and_then(|s| s.first())
+            .ok_or_else(|| anyhow!("there's no S to start from"))?;
+        // the plots, in reading order
+        Ok((garden.grid.into_keys().collect(), *start))
     }
 
     fn part1((pts, start): &mut (IndexSet<Pt<2>>, Pt<2>)) -> Result<usize> {
@@ -75,26 +64,27 @@ impl SolutionLinear<(IndexSet<Pt<2>>, Pt<2>), usize, usize> for Day21Solution {
     }
 
     // the number of areas reachable increases quadratically with steps. SUSPICIOUSLY, the number 26501365 is equal to (grid_size * 202300) + 65.
-    // so we can brute force finding the terms for our input, then find the 202300th term where t1 = c, t2 = c+grid_size, t3 = c+grid_size*2....
+    // so we find the first three terms t0 = c, t1 = c+grid_size, t2 = c+grid_size*2, then extrapolate out to the 202300th
     fn part2(
         (pts, start): &mut (IndexSet<Pt<2>>, Pt<2>),
         _part_1_solution: usize,
     ) -> Result<usize> {
         let grid_size = pts.last().unwrap().0[0] as usize + 1;
-        let t1 = steppin(pts, *start, 65);
-        let t2 = steppin(pts, *start, 65 + grid_size);
-        let t3 = steppin(pts, *start, 65 + grid_size + grid_size);
-
-        let (a, b) = find_best_fit(1, t2, 2, t3, t1);
+        let terms = (0..3)
+            .map(|i| steppin(pts, *start, PART_2_STEPS % grid_size + grid_size * i) as i128)
+            .collect_vec();
 
-        Ok((202300_usize.pow(2) * a) + (202300 * b) + t1)
+        Ok(usize::try_from(extrapolate(
+            &terms,
+            (PART_2_STEPS / grid_size) as i128,
+        ))?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Day21Solution;
-    use crate::utils::solver_types::SolutionLinear;
+    use crate::utils::{point::Pt, solver_types::SolutionLinear};
     use rstest::rstest;
 
     #[rstest]
@@ -123,4 +113,17 @@ mod tests {
         let p2 = Day21Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    #[test]
+    fn validate_load() {
+        let (plots, start) = Day21Solution::load("...\n.S#\n#..").unwrap();
+
+        assert_eq!(Pt([1, 1]), start);
+        // in reading order, so the last plot is the bottom right corner
+        assert_eq!(
+            vec![[0, 0], [1, 0], [2, 0], [0, 1], [1, 1], [1, 2], [2, 2]],
+            plots.iter().map(|p| p.0).collect::<Vec<_>>()
+        );
+        assert!(Day21Solution::load("...\n..#").is_err());
+    }
 }
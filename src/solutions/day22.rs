@@ -38,7 +38,7 @@ pub fn day22(input: &str) -> Result<f32> {
 
 /// from the bottom-most brick, check when its bottom plane would collide with a top plane, then set its z to the layer just above that.
 /// return a map of blocks to what blocks they rest upon
-fn settle(blocks: &mut Vec<Block>) -> HashMap<usize, Vec<usize>> {
+fn settle(blocks: &mut [Block]) -> HashMap<usize, Vec<usize>> {
     let mut m = HashMap::new();
     for i in 0..blocks.len() {
         if blocks[i].k.0[2] == 1 {
@@ -129,8 +129,8 @@ impl SolutionSimultaneous<Vec<Block>, usize, usize> for Day22Solution {
 
         // create a set of all bricks that are the sole support of another - these can't be disintegrated!
         let required = a_is_on_b
-            .iter()
-            .filter_map(|(_, vs)| if vs.len() == 1 { Some(vs[0]) } else { None })
+            .values()
+            .filter_map(|vs| if vs.len() == 1 { Some(vs[0]) } else { None })
             .unique()
             .collect_vec();
 
@@ -142,22 +142,39 @@ impl SolutionSimultaneous<Vec<Block>, usize, usize> for Day22Solution {
 
 #[cfg(test)]
 mod tests {
-    use super::Day22Solution;
-    use crate::utils::solver_types::SolutionSimultaneous;
+    use super::{settle, Block, Day22Solution};
+    use crate::utils::{grid::Grid, point::Pt, solver_types::SolutionSimultaneous};
+    use itertools::Itertools;
     use rstest::rstest;
 
-    #[rstest]
-    #[case(
-        "1,0,1~1,2,1
+    const EXAMPLE: &str = "1,0,1~1,2,1
 0,0,2~2,0,2
 0,2,3~2,2,3
 0,0,4~0,2,4
 2,0,5~2,2,5
 0,1,6~2,1,6
-1,1,8~1,1,9",
-        5,
-        7
-    )]
+1,1,8~1,1,9";
+
+    /// label each block with a letter, in the order they were loaded
+    fn tower(blocks: &[Block]) -> Grid<char, 3> {
+        let mut g = Grid::from(
+            blocks
+                .iter()
+                .zip('A'..)
+                .flat_map(|(b, c)| {
+                    (0..b.x_width)
+                        .cartesian_product(0..b.y_width)
+                        .cartesian_product(0..b.z_width)
+                        .map(move |((x, y), z)| (b.k + Pt([x, y, z]), c))
+                })
+                .collect_vec(),
+        );
+        g.set_default('.');
+        g
+    }
+
+    #[rstest]
+    #[case(EXAMPLE, 5, 7)]
     fn validate_day22(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
         let input = Day22Solution::load(input).unwrap();
 
@@ -165,4 +182,19 @@ mod tests {
         assert_eq!(expected_1, p1);
         assert_eq!(expected_2, p2);
     }
+
+    // the views of the settled tower given in the puzzle text, where '?' means several blocks are in line
+    #[rstest]
+    #[case(0, "\n.G.\n.G.\nFFF\nD.E\n???\n.A.\n")]
+    #[case(1, "\n.G.\n.G.\n.F.\n???\nB.C\nAAA\n")]
+    fn validate_day22_settled(#[case] axis: usize, #[case] expected: &str) {
+        let mut blocks = Day22Solution::load(EXAMPLE).unwrap();
+        settle(&mut blocks);
+
+        let view = tower(&blocks)
+            .project([axis, 2], |a, b| if a == b { a } else { '?' })
+            .flip(1);
+
+        assert_eq!(expected, view.print(|c| c));
+    }
 }
@@ -1,41 +1,88 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::{Ok, Result};
+use crate::utils::{
+    linalg::{solve, Matrix},
+    maths::{gcd, ray_intersection, Rational},
+    point::Pt,
+    solver_types::{solve_linear, SolutionLinear},
+};
+use anyhow::{anyhow, Ok, Result};
 use itertools::Itertools;
 
 pub struct Day24Solution {}
 
-type HailStone = ([f64; 3], [f64; 3]);
+type HailStone = (Pt<3>, Pt<3>);
 
 pub fn day24(input: &str) -> Result<f32> {
     solve_linear::<Day24Solution, _, _, _>(input)
 }
 
-fn find_collision(
-    ([x1, y1, _], [xv1, yv1, _]): HailStone,
-    ([x2, y2, _], [xv2, yv2, _]): HailStone,
-) -> Option<(f64, f64)> {
-    let m1 = yv1 / xv1;
-    let c1 = y1 - (m1 * x1);
-    let m2 = yv2 / xv2;
-    let c2 = y2 - (m2 * x2);
-    if m1 == m2 {
-        None
-    } else {
-        let d = (c2 - c1) / (m1 - m2);
-        Some((d, (m1 * d) + c1))
-    }
+fn flatten(pt: &Pt<3>) -> Pt<2> {
+    Pt([pt.0[0], pt.0[1]])
 }
 
-fn will_collide(
-    ([x1, y1, _], [xv1, yv1, _]): HailStone,
-    ([x2, y2, _], [xv2, yv2, _]): HailStone,
-    x: f64,
-    y: f64,
-) -> bool {
-    (x - x1).is_sign_negative() == xv1.is_sign_negative()
-        && (x - x2).is_sign_negative() == xv2.is_sign_negative()
-        && (y - y1).is_sign_negative() == yv1.is_sign_negative()
-        && (y - y2).is_sign_negative() == yv2.is_sign_negative()
+/// count the pairs of hailstones whose paths cross inside the test area, ignoring the z axis.
+/// the coordinates are big enough that floats lose precision, so we do this exactly
+fn count_crossings(stones: &[HailStone], min: isize, max: isize) -> usize {
+    let (min, max) = (Rational::from(min), Rational::from(max));
+
+    stones
+        .iter()
+        .tuple_combinations()
+        .filter(|((p1, v1), (p2, v2))| {
+            ray_intersection(flatten(p1), flatten(v1), flatten(p2), flatten(v2))
+                .is_some_and(|i| i.at.iter().all(|c| *c >= min && *c <= max))
+        })
+        .count()
+}
+
+/// a point's coordinates as i128s, with room for their products
+fn wide(pt: &Pt<3>) -> [i128; 3] {
+    pt.0.map(|v| v as i128)
+}
+
+/// in i128, as the cross product of two normals is around the square of the coordinates times the velocities
+fn cross([a1, a2, a3]: [i128; 3], [b1, b2, b3]: [i128; 3]) -> [i128; 3] {
+    [a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1]
+}
+
+/// where the rock has to start to hit every stone.
+/// seen from the first stone, which then sits still at the origin, the rock's path passes through the origin. so it lies
+/// in the plane through the origin and each other stone's path, and its direction is the line where two of those planes
+/// meet. working relative to the first stone like this keeps the numbers small enough to solve exactly in i128
+fn throw(stones: &[HailStone]) -> Option<[Rational; 3]> {
+    let (p0, v0) = stones.first()?;
+    let stones = stones[1..]
+        .iter()
+        .map(|(p, v)| (wide(&(*p - *p0)), wide(&(*v - *v0))))
+        .collect_vec();
+
+    // the rock's direction, as the smallest whole number vector along it
+    let direction = stones
+        .iter()
+        .map(|(p, v)| cross(*p, *v))
+        .tuple_combinations()
+        .map(|(n1, n2)| cross(n1, n2))
+        .find(|d| d.iter().any(|c| *c != 0))?;
+    let g = direction.iter().fold(0, |g, c| gcd(g, c.unsigned_abs())) as i128;
+    let direction = direction.map(|c| c / g);
+
+    // the rock is at w * direction when it hits a stone at time t: w * direction - t * v = p
+    let hits = stones
+        .iter()
+        .filter_map(|(p, v)| {
+            let rows = (0..3).map(|c| vec![direction[c], -v[c]]).collect_vec();
+            let rhs = p.map(Rational::from);
+            solve(&Matrix::from_rows(&rows), &rhs).map(|x| (x[0], x[1]))
+        })
+        .collect_vec();
+    let ((w1, t1), (w2, t2)) = hits
+        .iter()
+        .tuple_combinations()
+        .find(|((_, t1), (_, t2))| t1 != t2)?;
+
+    // the rock moves (w2 - w1) * direction between the two hits, so it's at w1 - speed * t1 at the start
+    let speed = (*w2 - *w1) / (*t2 - *t1);
+    let start = *w1 - speed * *t1;
+    Some([0, 1, 2].map(|c| start * Rational::from(direction[c]) + Rational::from(p0.0[c])))
 }
 
 impl SolutionLinear<Vec<HailStone>, usize, usize> for Day24Solution {
@@ -45,62 +92,67 @@ impl SolutionLinear<Vec<HailStone>, usize, usize> for Day24Solution {
             .map(|l| {
                 let (at, vel) = l.split_once(" @ ").unwrap();
                 (
-                    at.split(", ")
-                        .map(|v| v.trim().parse::<f64>().unwrap())
+                    Pt(at
+                        .split(", ")
+                        .map(|v| v.trim().parse().unwrap())
                         .collect_vec()
                         .try_into()
-                        .unwrap(),
-                    vel.split(", ")
-                        .map(|v| v.trim().parse::<f64>().unwrap())
+                        .unwrap()),
+                    Pt(vel
+                        .split(", ")
+                        .map(|v| v.trim().parse().unwrap())
                         .collect_vec()
                         .try_into()
-                        .unwrap(),
+                        .unwrap()),
                 )
             })
             .collect_vec())
     }
 
     fn part1(input: &mut Vec<HailStone>) -> Result<usize> {
-        // let min_x = 200_000_000_000_000.0;
-        // let max_x = 400_000_000_000_000.0;
-        // let min_y =200_000_000_000_000.0;
-        // let max_y = 400_000_000_000_000.0;
-        let min_x = 7.0;
-        let max_x = 27.0;
-        let min_y = 7.0;
-        let max_y = 27.0;
-        let mut matched = 0;
-        for i in 0..input.len() {
-            for j in i + 1..input.len() {
-                if let Some((cx, cy)) = find_collision(input[i], input[j]) {
-                    if will_collide(input[i], input[j], cx, cy)
-                        && cx >= min_x
-                        && cx <= max_x
-                        && cy >= min_y
-                        && cy <= max_y
-                    {
-                        matched += 1;
-                    }
-                }
-            }
-        }
-
-        Ok(matched)
+        Ok(count_crossings(
+            input,
+            200_000_000_000_000,
+            400_000_000_000_000,
+        ))
     }
 
-    fn part2(_input: &mut Vec<HailStone>, _part_1_solution: usize) -> Result<usize> {
-        todo!()
+    fn part2(input: &mut Vec<HailStone>, _part_1_solution: usize) -> Result<usize> {
+        let rock = throw(input).ok_or_else(|| anyhow!("no single throw hits every hailstone"))?;
+        let total = rock[0] + rock[1] + rock[2];
+        if !total.is_integer() {
+            return Err(anyhow!(
+                "the rock starts at a fractional position: {}",
+                total
+            ));
+        }
+        Ok(usize::try_from(total.num())?)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Day24Solution;
+    use super::{count_crossings, Day24Solution};
     use crate::utils::solver_types::SolutionLinear;
     use rstest::rstest;
 
+    #[test]
+    fn validate_day24_test_area() {
+        let input = Day24Solution::load(
+            "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3
+",
+        )
+        .unwrap();
+
+        assert_eq!(2, count_crossings(&input, 7, 27));
+    }
+
+    // none of the example's crossings are inside the real test area
     #[rstest]
-    #[ignore = "not done"]
     #[case(
         "19, 13, 30 @ -2,  1, -2
 18, 19, 22 @ -1, -1, -2
@@ -108,7 +160,7 @@ mod tests {
 12, 31, 28 @ -1, -2, -1
 20, 19, 15 @  1, -5, -3
 ",
-        2,
+        0,
         47
     )]
     fn validate_day24(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
@@ -120,4 +172,30 @@ mod tests {
         let p2 = Day24Solution::part2(&mut input, p1).unwrap();
         assert_eq!(expected_2, p2);
     }
+
+    // real inputs have coordinates in the hundreds of trillions, which overflowed when they went straight into the equations
+    #[rstest]
+    #[case(
+        "126323261116330, 388638022055069, 207034221151689 @ 84, 209, 336
+204851067145218, 388843963218501, 380428542953589 @ -261, 106, -178
+361254246633047, 319005418711007, 123381676717237 @ -294, 330, 271
+151756006607050, 368562925956719, 337753358942493 @ 93, 254, 95
+143021461488586, 314938681605677, 327846997803017 @ 37, 387, 25
+",
+        904447865666544
+    )]
+    #[case(
+        "238885196499962, 374961279691690, 394422676826963 @ -240, -285, -20
+173263854259218, 342290882149636, 391322966621714 @ 348, -179, 17
+289605264194978, 390445717594098, 368851846476589 @ -378, -31, 24
+133733693788144, 330036024003708, 287409783367054 @ 92, 83, 321
+222404641084097, 390806526689645, 332103203785712 @ -138, -377, 367
+",
+        944027283782270
+    )]
+    fn validate_realistic_throw(#[case] input: &str, #[case] expected: usize) {
+        let mut input = Day24Solution::load(input).unwrap();
+
+        assert_eq!(expected, Day24Solution::part2(&mut input, 0).unwrap());
+    }
 }
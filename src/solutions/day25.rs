@@ -1,15 +1,8 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::Write,
-};
-
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::Result;
-use petgraph::{
-    dot::{Config, Dot},
-    Graph, Undirected,
+use crate::utils::{
+    graph::{from_adjacency, min_cut, NamedGraph},
+    solver_types::{solve_linear, SolutionLinear},
 };
+use anyhow::{anyhow, Result};
 
 pub struct Day25Solution {}
 
@@ -17,64 +10,19 @@ pub fn day25(input: &str) -> Result<f32> {
     solve_linear::<Day25Solution, _, _, _>(input)
 }
 
-/// update to include bridges to remove
-const FILTER: [(&str, &str); 3] = [("hfx", "pzl"), ("bvb", "cmg"), ("nvd", "jqt")];
-
-impl SolutionLinear<Graph<String, usize, Undirected>, usize, String> for Day25Solution {
-    fn load(input: &str) -> Result<Graph<String, usize, Undirected>> {
-        let str_map: HashMap<String, Vec<String>> =
-            input.lines().fold(HashMap::new(), |mut hm, l| {
-                let (tag, oth) = l.split_once(": ").unwrap();
-                oth.split_whitespace().for_each(|o| {
-                    hm.entry(tag.to_owned()).or_default().push(o.to_owned());
-                    hm.entry(o.to_owned()).or_default().push(tag.to_owned());
-                });
-                hm
-            });
-
-        let mut g = Graph::new_undirected();
-        let mut ixs = HashMap::new();
-        for k in str_map.keys() {
-            ixs.insert(k, g.add_node(k.clone()));
-        }
-
-        for (k, vs) in str_map.iter() {
-            for v in vs {
-                if FILTER.contains(&(k, v)) || FILTER.contains(&(v, k)) {
-                    continue;
-                }
-                if !g.contains_edge(*ixs.get(v).unwrap(), *ixs.get(k).unwrap()) {
-                    g.add_edge(*ixs.get(k).unwrap(), *ixs.get(v).unwrap(), 0);
-                }
-            }
-        }
-
-        // turn this into something readable with `dot -Tsvg -Kneato test.dot > test.svg`
-        let mut f = File::create("test.dot").unwrap();
-        let output = format!("{}", Dot::with_config(&g, &[Config::EdgeNoLabel]));
-        let _ = f.write_all(output.as_bytes());
-
-        Ok(g)
+impl SolutionLinear<NamedGraph, usize, String> for Day25Solution {
+    fn load(input: &str) -> Result<NamedGraph> {
+        Ok(from_adjacency(input))
     }
 
-    fn part1(input: &mut Graph<String, usize, Undirected>) -> Result<usize> {
-        // pick node at random
-        let n = input.node_indices().next().unwrap();
-        let mut visited = HashSet::new();
-        let mut to_visit = vec![n];
-
-        while let Some(v) = to_visit.pop() {
-            visited.insert(v);
-            to_visit.extend(input.neighbors(v).filter(|n| !visited.contains(n)));
-        }
-
-        Ok(visited.len() * (input.node_count() - visited.len()))
+    /// the three wires to cut are the graph's minimum cut, which tells us the two groups directly
+    fn part1(input: &mut NamedGraph) -> Result<usize> {
+        let cut =
+            min_cut(input).ok_or_else(|| anyhow!("the graph needs at least two nodes to cut"))?;
+        Ok(cut.side.len() * (input.node_count() - cut.side.len()))
     }
 
-    fn part2(
-        _input: &mut Graph<String, usize, Undirected>,
-        _part_1_solution: usize,
-    ) -> Result<String> {
+    fn part2(_input: &mut NamedGraph, _part_1_solution: usize) -> Result<String> {
         Ok("Merry Christmas :)".to_string())
     }
 }
@@ -27,6 +27,8 @@ pub mod templates;
 
 use anyhow::Result;
 
+pub use day14::{spin_dish, Direction, SpinOptions};
+
 /// Add new solutions to this const
 pub const SOLUTIONS: [fn(&str) -> Result<f32>; 25] = [
     day01::day01,
@@ -0,0 +1,383 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::{BitAnd, BitOr, BitXor},
+};
+
+use super::point::Pt;
+
+const WORD: usize = u64::BITS as usize;
+
+/// A fixed-size 2D grid of booleans, packed into bits.
+/// Each row is stored as a whole number of words, so rows can be compared a word at a time.
+/// x increases rightward and y increases downward, as with `load_2d_grid`
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+#[allow(dead_code)]
+impl BitGrid {
+    /// create an empty grid
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = (width + WORD - 1) / WORD;
+        Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        }
+    }
+
+    /// create a grid with the given points set. points outside the grid are ignored
+    pub fn from_points<'a>(
+        width: usize,
+        height: usize,
+        pts: impl IntoIterator<Item = &'a Pt<2>>,
+    ) -> Self {
+        let mut grid = Self::new(width, height);
+        for Pt([x, y]) in pts {
+            if grid.in_bounds(*x, *y) {
+                grid.set(*x as usize, *y as usize, true);
+            }
+        }
+        grid
+    }
+
+    /// load a string representation of a grid, setting every point that is `on`
+    pub fn parse(input: &str, on: char) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = Self::new(width, lines.len());
+        for (y, l) in lines.iter().enumerate() {
+            for (x, c) in l.chars().enumerate() {
+                if c == on {
+                    grid.set(x, y, true);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// the word holding (x, y), and its bit in that word. an x past the width would quietly land in the padding bits
+    fn index(&self, x: usize, y: usize) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside a {}x{} grid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        (y * self.words_per_row + x / WORD, 1 << (x % WORD))
+    }
+
+    /// get a point. panics if it's outside the grid
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (i, mask) = self.index(x, y);
+        self.bits[i] & mask != 0
+    }
+
+    /// set a point. panics if it's outside the grid
+    pub fn set(&mut self, x: usize, y: usize, val: bool) {
+        let (i, mask) = self.index(x, y);
+        if val {
+            self.bits[i] |= mask;
+        } else {
+            self.bits[i] &= !mask;
+        }
+    }
+
+    /// get a point, treating anything outside the grid as unset
+    pub fn get_pt(&self, Pt([x, y]): &Pt<2>) -> bool {
+        self.in_bounds(*x, *y) && self.get(*x as usize, *y as usize)
+    }
+
+    /// every set point, in reading order
+    pub fn points(&self) -> impl Iterator<Item = Pt<2>> + '_ {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width)
+                .filter(move |x| self.get(*x, y))
+                .map(move |x| Pt([x as isize, y as isize]))
+        })
+    }
+
+    /// the words making up row y
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.bits[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// the number of points that differ between rows a and b
+    pub fn row_diff(&self, a: usize, b: usize) -> usize {
+        self.row(a)
+            .iter()
+            .zip(self.row(b))
+            .map(|(x, y)| (x ^ y).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn row_eq(&self, a: usize, b: usize) -> bool {
+        self.row(a) == self.row(b)
+    }
+
+    /// the number of points that differ between columns a and b
+    pub fn col_diff(&self, a: usize, b: usize) -> usize {
+        (0..self.height)
+            .filter(|y| self.get(a, *y) != self.get(b, *y))
+            .count()
+    }
+
+    pub fn col_eq(&self, a: usize, b: usize) -> bool {
+        self.col_diff(a, b) == 0
+    }
+
+    /// swap the x and y axes, so columns can be compared as rows
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::new(self.height, self.width);
+        for Pt([x, y]) in self.points() {
+            t.set(y as usize, x as usize, true);
+        }
+        t
+    }
+
+    /// the number of set points
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// move every point by (dx, dy). points that move outside the grid are lost
+    pub fn shift(&self, dx: isize, dy: isize) -> Self {
+        let mut shifted = Self::new(self.width, self.height);
+        for y in 0..self.height as isize {
+            let from = y - dy;
+            if from < 0 || from >= self.height as isize {
+                continue;
+            }
+            let row = shift_row(self.row(from as usize), dx);
+            let start = y as usize * self.words_per_row;
+            shifted.bits[start..start + self.words_per_row].copy_from_slice(&row);
+        }
+        shifted.clear_padding();
+        shifted
+    }
+
+    /// unset the bits past the end of each row, so they don't leak into comparisons
+    fn clear_padding(&mut self) {
+        let extra = self.width % WORD;
+        if extra == 0 {
+            return;
+        }
+        let mask = (1 << extra) - 1;
+        for y in 0..self.height {
+            self.bits[(y + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+
+    /// a cheap hash of the whole grid, for when storing every state would be too costly
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// print the grid, using # for set points and . for unset points
+    pub fn print(&self) -> String {
+        let mut res = String::from("\n");
+        for y in 0..self.height {
+            for x in 0..self.width {
+                res.push(if self.get(x, y) { '#' } else { '.' });
+            }
+            res.push('\n');
+        }
+        res
+    }
+
+    fn zip_with(&self, rhs: &Self, op: fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (rhs.width, rhs.height),
+            "grids must be the same size"
+        );
+        Self {
+            bits: self
+                .bits
+                .iter()
+                .zip(&rhs.bits)
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+            ..*self
+        }
+    }
+}
+
+/// shift the bits of a row towards higher x (or lower x if dx is negative)
+fn shift_row(row: &[u64], dx: isize) -> Vec<u64> {
+    let words = dx.unsigned_abs() / WORD;
+    let bits = dx.unsigned_abs() % WORD;
+    let len = row.len() as isize;
+    let get = |i: isize| {
+        if i >= 0 && i < len {
+            row[i as usize]
+        } else {
+            0
+        }
+    };
+
+    (0..len)
+        .map(|i| {
+            if dx >= 0 {
+                let src = i - words as isize;
+                let carry = if bits == 0 {
+                    0
+                } else {
+                    get(src - 1) >> (WORD - bits)
+                };
+                (get(src) << bits) | carry
+            } else {
+                let src = i + words as isize;
+                let carry = if bits == 0 {
+                    0
+                } else {
+                    get(src + 1) << (WORD - bits)
+                };
+                (get(src) >> bits) | carry
+            }
+        })
+        .collect()
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a ^ b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitGrid;
+    use crate::utils::point::Pt;
+    use rstest::rstest;
+
+    const PATTERN: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.";
+
+    #[test]
+    fn test_parse_and_print() {
+        let grid = BitGrid::parse(PATTERN, '#');
+
+        assert_eq!(format!("\n{}\n", PATTERN), grid.print());
+        assert_eq!((9, 7), (grid.width(), grid.height()));
+        assert_eq!(28, grid.count_ones());
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = BitGrid::parse(PATTERN, '#');
+
+        assert!(grid.row_eq(2, 3));
+        assert_eq!(1, grid.row_diff(0, 5));
+        assert!(grid.col_eq(4, 5));
+        assert_eq!(2, grid.col_diff(0, 1));
+        assert!(grid.transpose().row_eq(4, 5));
+        assert_eq!(grid, grid.transpose().transpose());
+    }
+
+    #[rstest]
+    #[case(1, 0, "\n.##\n..#\n...\n")]
+    #[case(-1, 0, "\n#..\n#..\n...\n")]
+    #[case(0, 1, "\n...\n##.\n.#.\n")]
+    #[case(0, -1, "\n.#.\n...\n...\n")]
+    #[case(5, 5, "\n...\n...\n...\n")]
+    fn test_shift(#[case] dx: isize, #[case] dy: isize, #[case] expected: &str) {
+        let grid = BitGrid::parse("##.\n.#.\n...", '#');
+
+        assert_eq!(expected, grid.shift(dx, dy).print());
+    }
+
+    #[rstest]
+    #[case(1)]
+    #[case(63)]
+    #[case(64)]
+    #[case(65)]
+    #[case(130)]
+    fn test_wide_shift(#[case] dx: isize) {
+        let pts = [Pt([0, 0]), Pt([62, 0]), Pt([63, 1]), Pt([100, 1])];
+        let grid = BitGrid::from_points(150, 2, &pts);
+
+        let expected = BitGrid::from_points(150, 2, &pts.map(|p| p + Pt([dx, 0])));
+
+        assert_eq!(expected, grid.shift(dx, 0));
+        assert_eq!(
+            BitGrid::from_points(150, 2, &pts.map(|p| p + Pt([-dx, 0]))),
+            grid.shift(-dx, 0)
+        );
+    }
+
+    #[test]
+    fn test_bit_ops() {
+        let a = BitGrid::parse("##.\n.#.", '#');
+        let b = BitGrid::parse("#.#\n.#.", '#');
+
+        assert_eq!("\n#..\n.#.\n", (&a & &b).print());
+        assert_eq!("\n###\n.#.\n", (&a | &b).print());
+        assert_eq!("\n.##\n...\n", (&a ^ &b).print());
+    }
+
+    #[test]
+    fn test_points_and_fingerprint() {
+        let pts = vec![Pt([0, 0]), Pt([2, 1]), Pt([1, 2])];
+        let grid = BitGrid::from_points(3, 3, &pts);
+
+        assert_eq!(pts, grid.points().collect::<Vec<_>>());
+        assert!(grid.get_pt(&Pt([2, 1])));
+        assert!(!grid.get_pt(&Pt([-1, 1])));
+        assert_eq!(grid.fingerprint(), grid.clone().fingerprint());
+        assert_ne!(grid.fingerprint(), grid.shift(1, 0).fingerprint());
+    }
+
+    #[test]
+    #[should_panic(expected = "(3, 0) is outside a 3x2 grid")]
+    fn test_out_of_bounds() {
+        // still inside the row's only word, so this would otherwise go unnoticed
+        BitGrid::new(3, 2).set(3, 0, true);
+    }
+}
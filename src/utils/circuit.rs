@@ -0,0 +1,459 @@
+//! Simulating networks of modules that pass high and low pulses to each other, like day 20's
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{anyhow, Result};
+
+use super::maths::{align_cycles, Cycle};
+
+/// The kind of a module, which decides what it does with the pulses it receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// pressed to start things off. sends a low pulse to the broadcaster
+    Button,
+    /// passes every pulse on to all of its outputs
+    Broadcaster,
+    /// `%`: ignores high pulses, and flips on or off with each low pulse, sending high when it turns on and low when it turns off
+    FlipFlop,
+    /// `&`: remembers the last pulse from each input. sends low if they were all high, and high otherwise
+    Conjunction,
+    /// anything that's sent pulses but never declared. it doesn't pass them on
+    Output,
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name: String,
+    pub kind: Kind,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+}
+
+/// A single pulse, between the modules with these ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    pub from: usize,
+    pub to: usize,
+    pub high: bool,
+}
+
+/// Everything that happened during one press of the button
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Press {
+    /// which press this was, counting from 1
+    pub number: usize,
+    /// every pulse sent, in the order they were handled
+    pub pulses: Vec<Pulse>,
+}
+
+#[allow(dead_code)]
+impl Press {
+    /// the number of [low, high] pulses sent
+    pub fn counts(&self) -> [usize; 2] {
+        let high = self.pulses.iter().filter(|p| p.high).count();
+        [self.pulses.len() - high, high]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    modules: Vec<Module>,
+    ids: HashMap<String, usize>,
+    /// whether each flip-flop is on
+    on: Vec<bool>,
+    /// for each conjunction, the last pulse from each of its inputs, in the same order as `inputs`
+    memory: Vec<Vec<bool>>,
+    presses: usize,
+    /// print every pulse as it's handled
+    pub trace: bool,
+}
+
+pub const BUTTON: &str = "button";
+pub const BROADCASTER: &str = "broadcaster";
+
+#[allow(dead_code)]
+impl Circuit {
+    /// load lines like `%a -> b, c`. `broadcaster` needs no prefix
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut declared = vec![];
+        for l in input.lines().filter(|l| !l.trim().is_empty()) {
+            let (module, outputs) = l
+                .split_once(" -> ")
+                .ok_or_else(|| anyhow!("expected `module -> outputs`, got '{}'", l))?;
+            let module = module.trim();
+            let (kind, name) = if let Some(name) = module.strip_prefix('%') {
+                (Kind::FlipFlop, name)
+            } else if let Some(name) = module.strip_prefix('&') {
+                (Kind::Conjunction, name)
+            } else if module == BROADCASTER {
+                (Kind::Broadcaster, BROADCASTER)
+            } else {
+                return Err(anyhow!("unknown module type in '{}'", module));
+            };
+            declared.push((
+                name,
+                kind,
+                outputs.split(',').map(str::trim).collect::<Vec<_>>(),
+            ));
+        }
+
+        let mut circuit = Circuit {
+            modules: vec![],
+            ids: HashMap::new(),
+            on: vec![],
+            memory: vec![],
+            presses: 0,
+            trace: false,
+        };
+        circuit.add(BUTTON, Kind::Button);
+        for (name, kind, _) in &declared {
+            if circuit.ids.contains_key(*name) {
+                return Err(anyhow!("module '{}' is declared twice", name));
+            }
+            circuit.add(name, *kind);
+        }
+        let broadcaster = circuit
+            .id(BROADCASTER)
+            .ok_or_else(|| anyhow!("there's no broadcaster"))?;
+        circuit.connect(0, broadcaster);
+
+        for (name, _, outputs) in &declared {
+            let from = circuit.ids[*name];
+            for o in outputs {
+                let to = match circuit.id(o) {
+                    Some(to) => to,
+                    None => circuit.add(o, Kind::Output),
+                };
+                circuit.connect(from, to);
+            }
+        }
+
+        circuit.reset();
+        Ok(circuit)
+    }
+
+    fn add(&mut self, name: &str, kind: Kind) -> usize {
+        self.modules.push(Module {
+            name: name.to_string(),
+            kind,
+            inputs: vec![],
+            outputs: vec![],
+        });
+        self.ids.insert(name.to_string(), self.modules.len() - 1);
+        self.modules.len() - 1
+    }
+
+    fn connect(&mut self, from: usize, to: usize) {
+        self.modules[from].outputs.push(to);
+        self.modules[to].inputs.push(from);
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn module(&self, id: usize) -> &Module {
+        &self.modules[id]
+    }
+
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+
+    /// turn every flip-flop off, make every conjunction remember low pulses, and reset the press count
+    pub fn reset(&mut self) {
+        self.on = vec![false; self.modules.len()];
+        self.memory = self
+            .modules
+            .iter()
+            .map(|m| vec![false; m.inputs.len()])
+            .collect();
+        self.presses = 0;
+    }
+
+    /// a pulse written the way the puzzle does, like `a -high-> b`
+    pub fn describe(&self, pulse: &Pulse) -> String {
+        format!(
+            "{} -{}-> {}",
+            self.modules[pulse.from].name,
+            if pulse.high { "high" } else { "low" },
+            self.modules[pulse.to].name
+        )
+    }
+
+    /// press the button, and handle pulses in the order they were sent until there are none left
+    pub fn press(&mut self) -> Press {
+        self.presses += 1;
+        let mut queue = VecDeque::from([Pulse {
+            from: 0,
+            to: self.ids[BROADCASTER],
+            high: false,
+        }]);
+        let mut pulses = vec![];
+
+        while let Some(pulse) = queue.pop_front() {
+            if self.trace {
+                println!("{}", self.describe(&pulse));
+            }
+            pulses.push(pulse);
+
+            let Pulse { from, to, high } = pulse;
+            let send = match self.modules[to].kind {
+                Kind::Broadcaster => Some(high),
+                Kind::FlipFlop if !high => {
+                    self.on[to] = !self.on[to];
+                    Some(self.on[to])
+                }
+                Kind::Conjunction => {
+                    let slot = self.modules[to]
+                        .inputs
+                        .iter()
+                        .position(|i| *i == from)
+                        .unwrap();
+                    self.memory[to][slot] = high;
+                    Some(!self.memory[to].iter().all(|m| *m))
+                }
+                Kind::FlipFlop | Kind::Button | Kind::Output => None,
+            };
+
+            if let Some(high) = send {
+                queue.extend(self.modules[to].outputs.iter().map(|o| Pulse {
+                    from: to,
+                    to: *o,
+                    high,
+                }));
+            }
+        }
+
+        if self.trace {
+            println!();
+        }
+        Press {
+            number: self.presses,
+            pulses,
+        }
+    }
+
+    /// The conjunction that sends `target` a low pulse exactly when all of its inputs have just sent it a high one.
+    /// it can be behind any number of inverters (conjunctions with a single input), which each flip the pulse that's needed
+    fn hub(&self, target: usize) -> Option<usize> {
+        // the module we're looking at, and whether it needs to be sent a low pulse (or a high one)
+        let (mut to, mut low) = (target, true);
+        for _ in 0..self.modules.len() {
+            let [from] = self.modules[to].inputs[..] else {
+                return None;
+            };
+            match (self.modules[from].kind, self.modules[from].inputs.len()) {
+                (Kind::Conjunction, 1) => (to, low) = (from, !low),
+                // a conjunction only sends high when any of its inputs is low, which isn't something counters can line up
+                (Kind::Conjunction, _) if low => return Some(from),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// When each of the sub-counters feeding `target` fires. `target` should be fed by a conjunction (possibly
+    /// through some inverters) that only gets the right pulse through to `target` when all of its inputs have just
+    /// sent it a high one. each input is watched from a fresh start until it has sent high twice, giving its offset and period
+    pub fn counter_cycles(&self, target: &str, max_presses: usize) -> Result<Vec<(String, Cycle)>> {
+        let target_id = self
+            .id(target)
+            .ok_or_else(|| anyhow!("there's no module called '{}'", target))?;
+        let hub = self.hub(target_id).ok_or_else(|| {
+            anyhow!(
+                "'{}' isn't fed by a conjunction of sub-counters, even through inverters",
+                target
+            )
+        })?;
+        let counters = self.modules[hub].inputs.clone();
+
+        let mut sim = self.clone();
+        sim.reset();
+        sim.trace = false;
+        let mut hits = vec![vec![]; counters.len()];
+        while hits.iter().any(|h| h.len() < 2) {
+            if sim.presses >= max_presses {
+                return Err(anyhow!(
+                    "the counters feeding '{}' didn't all repeat within {} presses",
+                    target,
+                    max_presses
+                ));
+            }
+            let press = sim.press();
+            for (i, c) in counters.iter().enumerate() {
+                if press
+                    .pulses
+                    .iter()
+                    .any(|p| p.high && p.from == *c && p.to == hub)
+                    && hits[i].len() < 2
+                {
+                    hits[i].push(press.number as i128);
+                }
+            }
+        }
+
+        Ok(counters
+            .iter()
+            .zip(hits)
+            .map(|(c, h)| (self.modules[*c].name.clone(), Cycle::new(h[0], h[1] - h[0])))
+            .collect())
+    }
+
+    /// the first press that sends `target` a low pulse, found by lining up the sub-counters that feed it
+    pub fn first_low_to(&self, target: &str, max_presses: usize) -> Result<usize> {
+        let cycles = self.counter_cycles(target, max_presses)?;
+        let first = align_cycles(cycles.into_iter().map(|(_, c)| c))
+            .ok_or_else(|| anyhow!("the counters feeding '{}' never line up", target))?;
+        Ok(usize::try_from(first)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Circuit;
+    use rstest::rstest;
+
+    const LOOP: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const OUTPUT: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    #[rstest]
+    #[case(
+        LOOP,
+        "button -low-> broadcaster
+broadcaster -low-> a
+broadcaster -low-> b
+broadcaster -low-> c
+a -high-> b
+b -high-> c
+c -high-> inv
+inv -low-> a
+a -low-> b
+b -low-> c
+c -low-> inv
+inv -high-> a"
+    )]
+    #[case(
+        OUTPUT,
+        "button -low-> broadcaster
+broadcaster -low-> a
+a -high-> inv
+a -high-> con
+inv -low-> b
+con -high-> output
+b -high-> con
+con -low-> output"
+    )]
+    fn validate_trace(#[case] input: &str, #[case] expected: &str) {
+        let mut circuit = Circuit::parse(input).unwrap();
+        let press = circuit.press();
+
+        assert_eq!(
+            expected,
+            press
+                .pulses
+                .iter()
+                .map(|p| circuit.describe(p))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case(LOOP, 32_000_000)]
+    #[case(OUTPUT, 11_687_500)]
+    fn validate_counts(#[case] input: &str, #[case] expected: usize) {
+        let mut circuit = Circuit::parse(input).unwrap();
+        let [low, high] = (0..1000).fold([0, 0], |[l, h], _| {
+            let [pl, ph] = circuit.press().counts();
+            [l + pl, h + ph]
+        });
+
+        assert_eq!(expected, low * high);
+        assert_eq!(1000, circuit.presses());
+    }
+
+    // x inverts a, which flips every press, so x sends high every 2nd press.
+    // y inverts c, which flips every 2nd press, so y sends high every 4th
+    #[rstest]
+    #[case(
+        "broadcaster -> a, b
+%a -> x
+%b -> c
+%c -> y
+&x -> hub
+&y -> hub
+&hub -> rx"
+    )]
+    // the same counters, with two inverters between the hub and rx
+    #[case(
+        "broadcaster -> a, b
+%a -> x
+%b -> c
+%c -> y
+&x -> hub
+&y -> hub
+&hub -> inv
+&inv -> out
+&out -> rx"
+    )]
+    fn validate_counter_cycles(#[case] input: &str) {
+        let circuit = Circuit::parse(input).unwrap();
+
+        let cycles = circuit.counter_cycles("rx", 100).unwrap();
+        assert_eq!(
+            vec![("x".to_string(), 2, 2), ("y".to_string(), 4, 4)],
+            cycles
+                .into_iter()
+                .map(|(n, c)| (n, c.offset, c.period))
+                .collect::<Vec<_>>()
+        );
+
+        let first = circuit.first_low_to("rx", 100).unwrap();
+        let mut sim = circuit.clone();
+        let rx = sim.id("rx").unwrap();
+        let simulated = (1..)
+            .find(|_| sim.press().pulses.iter().any(|p| p.to == rx && !p.high))
+            .unwrap();
+        assert_eq!(simulated, first);
+    }
+
+    #[rstest]
+    #[case("broadcaster -> a\n%a -> b\n%a -> c")]
+    #[case("%a -> b")]
+    #[case("broadcaster a")]
+    #[case("broadcaster -> a\n$a -> b")]
+    fn validate_bad_circuits(#[case] input: &str) {
+        assert!(Circuit::parse(input).is_err());
+    }
+
+    #[test]
+    fn validate_no_counters() {
+        let circuit = Circuit::parse(OUTPUT).unwrap();
+
+        assert!(circuit.counter_cycles("con", 100).is_err());
+        assert!(circuit.counter_cycles("nowhere", 100).is_err());
+
+        // rx gets a low pulse whenever either of x or y is low, so there's nothing to line up
+        let circuit = Circuit::parse(
+            "broadcaster -> a, b
+%a -> x
+%b -> y
+&x -> hub
+&y -> hub
+&hub -> inv
+&inv -> rx",
+        )
+        .unwrap();
+        assert!(circuit.counter_cycles("rx", 100).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Finding where an iterated process starts repeating itself, so we can skip ahead to step 1,000,000,000
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The shape of a repeating sequence: after `offset` steps of run-up, it loops every `period` steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat {
+    pub offset: usize,
+    pub period: usize,
+}
+
+#[allow(dead_code)]
+impl Repeat {
+    /// the earliest step with the same state as step n
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.offset {
+            n
+        } else {
+            self.offset + (n - self.offset) % self.period
+        }
+    }
+}
+
+/// Every state up to the first repeat, so any later step can be looked up without simulating it
+#[derive(Debug, Clone)]
+pub struct History<S> {
+    pub repeat: Repeat,
+    states: Vec<S>,
+}
+
+#[allow(dead_code)]
+impl<S> History<S> {
+    /// the state after n steps
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.repeat.equivalent_step(n)]
+    }
+
+    /// the states before the first repeat, from the initial state onwards
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+}
+
+/// step from `initial` until a state comes round again, remembering each state by its `key`.
+/// the key only needs to tell states apart - a cheaper or smaller stand-in for the state itself
+#[allow(dead_code)]
+pub fn detect<S, K: Hash + Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> History<S> {
+    let mut seen = HashMap::from([(key(&initial), 0)]);
+    let mut states = vec![initial];
+
+    loop {
+        let next = step(states.last().unwrap());
+        let k = key(&next);
+        if let Some(first) = seen.get(&k) {
+            return History {
+                repeat: Repeat {
+                    offset: *first,
+                    period: states.len() - first,
+                },
+                states,
+            };
+        }
+        seen.insert(k, states.len());
+        states.push(next);
+    }
+}
+
+/// Brent's algorithm: find where a sequence repeats while only holding a couple of states at a time.
+/// takes more steps than `detect`, so is best when the states are big and the steps are cheap
+#[allow(dead_code)]
+pub fn brent<S: Clone + PartialEq>(initial: S, step: impl Fn(&S) -> S) -> Repeat {
+    // find the period, by racing a hare ahead of a tortoise that teleports at each power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // then walk two states a period apart until they meet, which is where the loop starts
+    let mut tortoise = initial.clone();
+    let mut hare = (0..period).fold(initial, |s, _| step(&s));
+    let mut offset = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        offset += 1;
+    }
+
+    Repeat { offset, period }
+}
+
+/// the state after n steps, skipping over as many loops as possible
+#[allow(dead_code)]
+pub fn state_at<S: Clone + Hash + Eq>(initial: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    detect(initial, step, S::clone).state_at(n).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brent, detect, state_at, Repeat};
+    use rstest::rstest;
+
+    // x -> x^2 + 1 mod m is a classic rho shape
+    fn rho(m: u64) -> impl Fn(&u64) -> u64 {
+        move |x| (x * x + 1) % m
+    }
+
+    #[rstest]
+    // 3, 10, 5, 2, 5, ...
+    #[case(3, 24, Repeat { offset: 2, period: 2 })]
+    // 0, 1, 2, 5, 26, 677, 330, 901, 802, 205, 26, ...
+    #[case(0, 1000, Repeat { offset: 4, period: 6 })]
+    // 7, 1, 2, 5, 5, ...
+    #[case(7, 7, Repeat { offset: 3, period: 1 })]
+    fn validate_repeat(#[case] initial: u64, #[case] m: u64, #[case] expected: Repeat) {
+        assert_eq!(expected, detect(initial, rho(m), |x| *x).repeat);
+        assert_eq!(expected, brent(initial, rho(m)));
+    }
+
+    #[test]
+    fn validate_state_at() {
+        let f = rho(1000);
+        let history = detect(0, &f, |x| *x);
+        let simulated = (0..100).fold(0, |x, _| f(&x));
+
+        assert_eq!(&simulated, history.state_at(100));
+        assert_eq!(&5, history.state_at(3));
+        assert_eq!(901, state_at(0, &f, 1_000_000_003));
+    }
+}
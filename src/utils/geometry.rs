@@ -0,0 +1,100 @@
+//! Polygons on the integer lattice, measured from their corners rather than by filling in every cell
+
+use super::{
+    maths::{gcd, Rational},
+    point::Pt,
+};
+
+/// the corners visited by starting at `start` and making each move in turn.
+/// a move is the whole offset, so a direction scaled by its distance
+#[allow(dead_code)]
+pub fn trace(start: Pt<2>, moves: impl IntoIterator<Item = Pt<2>>) -> Vec<Pt<2>> {
+    let mut at = start;
+    let mut vertices = vec![start];
+    for m in moves {
+        at += m;
+        vertices.push(at);
+    }
+    vertices
+}
+
+/// twice the area of a polygon, by the shoelace formula. always a whole number for lattice points.
+/// the last vertex joins back up to the first, and it doesn't matter which way round they go
+#[allow(dead_code)]
+pub fn double_area(vertices: &[Pt<2>]) -> i128 {
+    let shoelace: i128 = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(Pt([x1, y1]), Pt([x2, y2]))| *x1 as i128 * *y2 as i128 - *x2 as i128 * *y1 as i128)
+        .sum();
+    shoelace.abs()
+}
+
+#[allow(dead_code)]
+pub fn area(vertices: &[Pt<2>]) -> Rational {
+    Rational::new(double_area(vertices), 2)
+}
+
+/// the number of lattice points on the edges of a polygon.
+/// when every edge is horizontal or vertical, this is also the length of its boundary
+#[allow(dead_code)]
+pub fn boundary_points(vertices: &[Pt<2>]) -> i128 {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let Pt([dx, dy]) = *b - *a;
+            gcd(dx.unsigned_abs() as u128, dy.unsigned_abs() as u128) as i128
+        })
+        .sum()
+}
+
+/// the number of lattice points strictly inside a polygon, by Pick's theorem: A = I + B/2 - 1
+#[allow(dead_code)]
+pub fn interior_points(vertices: &[Pt<2>]) -> i128 {
+    (double_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{area, boundary_points, double_area, interior_points, trace};
+    use crate::utils::{maths::Rational, point::Pt};
+    use rstest::rstest;
+
+    #[rstest]
+    // a 4x3 rectangle, both ways round
+    #[case(vec![Pt([0, 0]), Pt([4, 0]), Pt([4, 3]), Pt([0, 3])], 24, 14, 6)]
+    #[case(vec![Pt([0, 3]), Pt([4, 3]), Pt([4, 0]), Pt([0, 0])], 24, 14, 6)]
+    // a triangle with a sloped edge
+    #[case(vec![Pt([0, 0]), Pt([4, 0]), Pt([0, 2])], 8, 8, 1)]
+    // an L shape
+    #[case(vec![Pt([0, 0]), Pt([2, 0]), Pt([2, 1]), Pt([1, 1]), Pt([1, 2]), Pt([0, 2])], 6, 8, 0)]
+    fn validate_polygon(
+        #[case] vertices: Vec<Pt<2>>,
+        #[case] expected_double_area: i128,
+        #[case] expected_boundary: i128,
+        #[case] expected_interior: i128,
+    ) {
+        assert_eq!(expected_double_area, double_area(&vertices));
+        assert_eq!(Rational::new(expected_double_area, 2), area(&vertices));
+        assert_eq!(expected_boundary, boundary_points(&vertices));
+        assert_eq!(expected_interior, interior_points(&vertices));
+    }
+
+    #[test]
+    fn validate_trace() {
+        let vertices = trace(
+            Pt([1, 1]),
+            [Pt([3, 0]), Pt([0, 2]), Pt([-3, 0]), Pt([0, -2])],
+        );
+
+        assert_eq!(
+            vec![Pt([1, 1]), Pt([4, 1]), Pt([4, 3]), Pt([1, 3]), Pt([1, 1])],
+            vertices
+        );
+        // closing the loop explicitly doesn't change anything
+        assert_eq!(12, double_area(&vertices));
+        assert_eq!(10, boundary_points(&vertices));
+        assert_eq!(2, interior_points(&vertices));
+    }
+}
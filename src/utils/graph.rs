@@ -0,0 +1,276 @@
+//! Undirected graphs built on petgraph: loading them from adjacency lists, and finding the weak points in them
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::{
+    dot::{Config, Dot},
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Graph, Undirected,
+};
+use priority_queue::PriorityQueue;
+
+/// An undirected graph of named nodes, where each edge has a weight
+pub type NamedGraph = Graph<String, usize, Undirected>;
+
+/// load lines like `name: neighbour neighbour ...` into a graph.
+/// each connection becomes a single edge of weight 1, however many times it's listed
+#[allow(dead_code)]
+pub fn from_adjacency(input: &str) -> NamedGraph {
+    let mut g = NamedGraph::new_undirected();
+    let mut ixs: HashMap<&str, NodeIndex> = HashMap::new();
+
+    for l in input.lines().filter(|l| !l.trim().is_empty()) {
+        let (name, neighbours) = l.split_once(':').unwrap_or((l, ""));
+        let name = name.trim();
+        let a = *ixs
+            .entry(name)
+            .or_insert_with(|| g.add_node(name.to_string()));
+        for n in neighbours.split_whitespace() {
+            let b = *ixs.entry(n).or_insert_with(|| g.add_node(n.to_string()));
+            if a != b && g.find_edge(a, b).is_none() {
+                g.add_edge(a, b, 1);
+            }
+        }
+    }
+    g
+}
+
+/// A way of splitting a graph in two
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// the total weight of the edges that cross the cut
+    pub weight: usize,
+    /// the nodes on one side of the cut. everything else is on the other
+    pub side: HashSet<NodeIndex>,
+    pub edges: Vec<EdgeIndex>,
+}
+
+/// the lightest set of edges whose removal splits the graph in two, by the Stoer-Wagner algorithm.
+/// None if there are fewer than 2 nodes
+#[allow(dead_code)]
+pub fn min_cut<N>(g: &Graph<N, usize, Undirected>) -> Option<Cut> {
+    let n = g.node_count();
+    if n < 2 {
+        return None;
+    }
+
+    // as nodes are merged together, keep track of the total weight between each merged group
+    let mut adj: Vec<HashMap<usize, usize>> = vec![HashMap::new(); n];
+    for e in g.edge_references() {
+        let (a, b) = (e.source().index(), e.target().index());
+        if a != b {
+            *adj[a].entry(b).or_default() += e.weight();
+            *adj[b].entry(a).or_default() += e.weight();
+        }
+    }
+    let mut members: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+    let mut alive: Vec<usize> = (0..n).collect();
+    let mut best: Option<(usize, Vec<usize>)> = None;
+
+    while alive.len() > 1 {
+        // maximum adjacency search: repeatedly take the node most tightly connected to those already taken
+        let mut queue: PriorityQueue<usize, usize> = alive.iter().map(|v| (*v, 0)).collect();
+        let (mut s, mut t, mut cut_of_phase) = (alive[0], alive[0], 0);
+        while let Some((v, w)) = queue.pop() {
+            (s, t, cut_of_phase) = (t, v, w);
+            for (u, wt) in &adj[v] {
+                queue.change_priority_by(u, |p| *p += wt);
+            }
+        }
+
+        // the last node taken, cut off from everything else, is the best cut that separates s and t
+        if best.as_ref().map_or(true, |(w, _)| cut_of_phase < *w) {
+            best = Some((cut_of_phase, members[t].clone()));
+        }
+
+        // so now we can treat s and t as one node
+        for (u, wt) in std::mem::take(&mut adj[t]) {
+            adj[u].remove(&t);
+            if u != s {
+                *adj[s].entry(u).or_default() += wt;
+                *adj[u].entry(s).or_default() += wt;
+            }
+        }
+        let merged = std::mem::take(&mut members[t]);
+        members[s].extend(merged);
+        alive.retain(|v| *v != t);
+    }
+
+    let (weight, side) = best?;
+    let side: HashSet<NodeIndex> = side.into_iter().map(NodeIndex::new).collect();
+    let edges = g
+        .edge_references()
+        .filter(|e| side.contains(&e.source()) != side.contains(&e.target()))
+        .map(|e| e.id())
+        .collect();
+    Some(Cut {
+        weight,
+        side,
+        edges,
+    })
+}
+
+/// Tarjan's low-link search: the edges and nodes whose removal would disconnect part of the graph
+fn low_links<N, E>(g: &Graph<N, E, Undirected>) -> (Vec<EdgeIndex>, Vec<NodeIndex>) {
+    let n = g.node_count();
+    let mut disc: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut timer = 0;
+    let mut bridges = vec![];
+    let mut cut_vertices = HashSet::new();
+
+    for root in g.node_indices() {
+        if disc[root.index()].is_some() {
+            continue;
+        }
+        disc[root.index()] = Some(timer);
+        low[root.index()] = timer;
+        timer += 1;
+        let mut root_children = 0;
+
+        // (node, the edge we arrived by, the edges left to look down)
+        let mut stack = vec![(root, None, g.edges(root).collect::<Vec<_>>())];
+        while let Some((v, parent_edge, edges)) = stack.last_mut() {
+            let v = *v;
+            let Some(e) = edges.pop() else {
+                let parent_edge = *parent_edge;
+                stack.pop();
+                // pass our low link back up to the node we came from
+                if let (Some(e), Some((p, _, _))) = (parent_edge, stack.last()) {
+                    let p = *p;
+                    low[p.index()] = low[p.index()].min(low[v.index()]);
+                    let p_disc = disc[p.index()].unwrap();
+                    if low[v.index()] > p_disc {
+                        bridges.push(e);
+                    }
+                    if p == root {
+                        root_children += 1;
+                    } else if low[v.index()] >= p_disc {
+                        cut_vertices.insert(p);
+                    }
+                }
+                continue;
+            };
+
+            let u = if e.source() == v {
+                e.target()
+            } else {
+                e.source()
+            };
+            if u == v || Some(e.id()) == *parent_edge {
+                continue;
+            }
+            match disc[u.index()] {
+                Some(d) => low[v.index()] = low[v.index()].min(d),
+                None => {
+                    disc[u.index()] = Some(timer);
+                    low[u.index()] = timer;
+                    timer += 1;
+                    stack.push((u, Some(e.id()), g.edges(u).collect()));
+                }
+            }
+        }
+
+        if root_children > 1 {
+            cut_vertices.insert(root);
+        }
+    }
+
+    (bridges, cut_vertices.into_iter().collect())
+}
+
+/// the edges whose removal would split the graph into more pieces
+#[allow(dead_code)]
+pub fn bridges<N, E>(g: &Graph<N, E, Undirected>) -> Vec<EdgeIndex> {
+    let mut bridges = low_links(g).0;
+    bridges.sort();
+    bridges
+}
+
+/// the nodes whose removal would split the graph into more pieces
+#[allow(dead_code)]
+pub fn articulation_points<N, E>(g: &Graph<N, E, Undirected>) -> Vec<NodeIndex> {
+    let mut points = low_links(g).1;
+    points.sort();
+    points
+}
+
+/// the graph in Graphviz's DOT format, without edge labels.
+/// turn it into something readable with `dot -Tsvg -Kneato graph.dot > graph.svg`
+#[allow(dead_code)]
+pub fn to_dot<N: std::fmt::Debug, E: std::fmt::Debug>(g: &Graph<N, E, Undirected>) -> String {
+    format!("{:?}", Dot::with_config(g, &[Config::EdgeNoLabel]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{articulation_points, bridges, from_adjacency, min_cut, to_dot, NamedGraph};
+    use itertools::Itertools;
+    use rstest::rstest;
+
+    fn names(
+        g: &NamedGraph,
+        ixs: impl IntoIterator<Item = petgraph::graph::NodeIndex>,
+    ) -> Vec<&str> {
+        ixs.into_iter().map(|i| g[i].as_str()).sorted().collect()
+    }
+
+    fn edge_names(g: &NamedGraph, es: &[petgraph::graph::EdgeIndex]) -> Vec<String> {
+        es.iter()
+            .map(|e| {
+                let (a, b) = g.edge_endpoints(*e).unwrap();
+                names(g, [a, b]).join("-")
+            })
+            .sorted()
+            .collect()
+    }
+
+    #[test]
+    fn validate_from_adjacency() {
+        let g = from_adjacency("a: b c\nb: a\nc: d\n");
+
+        assert_eq!(4, g.node_count());
+        // a-b is listed twice, but only added once
+        assert_eq!(3, g.edge_count());
+    }
+
+    #[rstest]
+    // two triangles joined by a single edge
+    #[case("a: b c\nb: c\nc: d\nd: e f\ne: f", 1, vec!["c-d"])]
+    // two complete graphs of 4 nodes, joined by two edges
+    #[case("a: b c d\nb: c d f\nc: d\nd: e\ne: f g h\nf: g h\ng: h", 2, vec!["b-f", "d-e"])]
+    fn validate_min_cut(#[case] input: &str, #[case] weight: usize, #[case] edges: Vec<&str>) {
+        let g = from_adjacency(input);
+        let cut = min_cut(&g).unwrap();
+
+        assert_eq!(weight, cut.weight);
+        assert_eq!(edges, edge_names(&g, &cut.edges));
+        assert_eq!(g.node_count() / 2, cut.side.len());
+    }
+
+    #[test]
+    fn validate_bridges() {
+        // a triangle, with a tail off one corner and a second tail off the end of that
+        let g = from_adjacency("a: b c\nb: c\nc: d\nd: e f");
+
+        assert_eq!(vec!["c-d", "d-e", "d-f"], edge_names(&g, &bridges(&g)));
+        assert_eq!(vec!["c", "d"], names(&g, articulation_points(&g)));
+    }
+
+    #[test]
+    fn validate_no_bridges() {
+        let g = from_adjacency("a: b c\nb: c d\nc: d");
+
+        assert!(bridges(&g).is_empty());
+        assert!(articulation_points(&g).is_empty());
+    }
+
+    #[test]
+    fn validate_dot() {
+        let dot = to_dot(&from_adjacency("a: b"));
+
+        assert!(dot.starts_with("graph {"));
+        assert!(dot.contains("0 -- 1"));
+    }
+}
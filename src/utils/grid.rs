@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Debug;
 use std::vec::IntoIter;
 
@@ -75,7 +75,12 @@ impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
     }
 
     /// apply a transformation to every point in a grid
-    pub fn transform(mut self, transformation: fn(Pt<DIMS>) -> Pt<DIMS>) -> Self {
+    pub fn transform(self, transformation: fn(Pt<DIMS>) -> Pt<DIMS>) -> Self {
+        self.remap(transformation)
+    }
+
+    /// like `transform`, but accepts closures so we can capture things like the grid's bounds
+    fn remap(mut self, transformation: impl Fn(Pt<DIMS>) -> Pt<DIMS>) -> Self {
         let mut new_grid = IndexMap::default();
         self.grid.into_iter().for_each(|(k, v)| {
             new_grid.insert(transformation(k), v);
@@ -84,6 +89,69 @@ impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
         self
     }
 
+    /// mirror the grid along the given axis, keeping it within its current bounds
+    pub fn flip(self, axis: usize) -> Self {
+        assert!(
+            axis < DIMS,
+            "can't flip along axis {} of a {}D grid",
+            axis,
+            DIMS
+        );
+        if self.grid.is_empty() {
+            return self;
+        }
+        let (mins, maxs) = self.bounds();
+        self.remap(|mut pt| {
+            pt.0[axis] = mins[axis] + maxs[axis] - pt.0[axis];
+            pt
+        })
+    }
+
+    /// translate the grid so that its minimum coordinate in each dimension is 0
+    pub fn normalise(self) -> Self {
+        if self.grid.is_empty() {
+            return self;
+        }
+        let (mins, _) = self.bounds();
+        self.remap(|pt| pt - Pt(mins))
+    }
+
+    /// discard every point outside of the given (inclusive) bounds
+    pub fn crop(mut self, mins: [isize; DIMS], maxs: [isize; DIMS]) -> Self {
+        self.grid
+            .retain(|k, _| (0..DIMS).all(|i| k.0[i] >= mins[i] && k.0[i] <= maxs[i]));
+        self
+    }
+
+    /// repeat the grid `counts[i]` times along each dimension i, e.g. for simulating infinitely repeating maps
+    pub fn tile(mut self, counts: [usize; DIMS]) -> Self {
+        if self.grid.is_empty() {
+            return self;
+        }
+        let (mins, maxs) = self.bounds();
+        let mut sizes = [0; DIMS];
+        for i in 0..DIMS {
+            sizes[i] = maxs[i] - mins[i] + 1;
+        }
+
+        let mut new_grid = IndexMap::default();
+        for repeat in counts
+            .iter()
+            .map(|c| 0..*c as isize)
+            .multi_cartesian_product()
+        {
+            let mut offset = [0; DIMS];
+            for i in 0..DIMS {
+                offset[i] = repeat[i] * sizes[i];
+            }
+            self.grid.iter().for_each(|(k, v)| {
+                new_grid.insert(k + &Pt(offset), *v);
+            });
+        }
+        self.grid = new_grid;
+        self
+    }
+
     /// get the min and max values of each dimension
     pub fn bounds(&self) -> ([isize; DIMS], [isize; DIMS]) {
         let mut mins = [isize::MAX; DIMS];
@@ -107,14 +175,281 @@ impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
                 .unwrap()
         })
     }
+
+    /// create an empty grid of any dimension with the same default value as this one
+    fn empty_like<const N: usize>(&self) -> Grid<T, N> {
+        Grid {
+            offsets: Pt::<N>::neighbour_offsets(),
+            card_offsets: Pt::<N>::card_offsets(),
+            default_val: self.default_val,
+            grid: IndexMap::default(),
+        }
+    }
+
+    /// extract the 2D plane spanned by `axes` that passes through `at`.
+    /// the coordinates of `at` along `axes` are ignored
+    pub fn slice(&self, axes: [usize; 2], at: &Pt<DIMS>) -> Grid<T, 2> {
+        let mut plane = self.empty_like();
+        for (k, v) in self.grid.iter() {
+            if (0..DIMS).all(|i| axes.contains(&i) || k.0[i] == at.0[i]) {
+                plane.grid.insert(Pt([k.0[axes[0]], k.0[axes[1]]]), *v);
+            }
+        }
+        plane
+    }
+
+    /// flatten the grid onto the 2D plane spanned by `axes`, combining values that land on the same point.
+    /// e.g. use `max` to see the highest value along the other axes, or `||` to see if any point is set
+    pub fn project(&self, axes: [usize; 2], combine: fn(T, T) -> T) -> Grid<T, 2> {
+        let mut plane = self.empty_like();
+        for (k, v) in self.grid.iter() {
+            let pt = Pt([k.0[axes[0]], k.0[axes[1]]]);
+            let new_val = match plane.grid.get(&pt) {
+                Some(old) => combine(*old, *v),
+                None => *v,
+            };
+            plane.grid.insert(pt, new_val);
+        }
+        plane
+    }
+
+    /// print each x/y layer of a grid, labelled by its coordinates in the remaining dimensions (e.g. `z=0, w=1`)
+    /// every layer is printed with the same x/y bounds so they line up
+    pub fn print_nd(&self, to_printable: fn(T) -> char) -> String {
+        assert!(DIMS >= 2, "can only print grids with 2 or more dimensions");
+
+        let mut res = String::new();
+        if self.grid.is_empty() {
+            return res;
+        }
+        let (mins, maxs) = self.bounds();
+
+        let layers = if DIMS == 2 {
+            vec![vec![]]
+        } else {
+            (2..DIMS)
+                .map(|i| mins[i]..maxs[i] + 1)
+                .multi_cartesian_product()
+                .collect_vec()
+        };
+
+        for layer in layers {
+            let mut at = Pt([0; DIMS]);
+            at.0[2..].copy_from_slice(&layer);
+
+            if !layer.is_empty() {
+                res.push('\n');
+                res.push_str(
+                    &(2..)
+                        .zip(layer)
+                        .map(|(i, v)| format!("{}={}", axis_name(i), v))
+                        .join(", "),
+                );
+            }
+            res.push_str(&self.slice([0, 1], &at).print_within(
+                [mins[0], mins[1]],
+                [maxs[0], maxs[1]],
+                to_printable,
+            ));
+        }
+
+        res
+    }
+}
+
+/// region analysis
+#[allow(dead_code)]
+impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
+    /// find every point reachable from `start` through cardinal steps onto points for which `can_enter` holds.
+    /// the fill is limited to the grid's bounds, so it can be used to explore empty (default) space too
+    pub fn flood_fill(
+        &self,
+        start: Pt<DIMS>,
+        can_enter: impl Fn(&Pt<DIMS>, T) -> bool,
+    ) -> HashSet<Pt<DIMS>> {
+        let (mins, maxs) = self.bounds();
+        self.flood_within(start, mins, maxs, can_enter)
+    }
+
+    fn flood_within(
+        &self,
+        start: Pt<DIMS>,
+        mins: [isize; DIMS],
+        maxs: [isize; DIMS],
+        can_enter: impl Fn(&Pt<DIMS>, T) -> bool,
+    ) -> HashSet<Pt<DIMS>> {
+        let mut filled = HashSet::from([start]);
+        let mut to_visit = VecDeque::from([start]);
+
+        while let Some(pt) = to_visit.pop_front() {
+            for off in &self.card_offsets {
+                let next = &pt + off;
+                if (0..DIMS).all(|i| next.0[i] >= mins[i] && next.0[i] <= maxs[i])
+                    && !filled.contains(&next)
+                    && can_enter(&next, self.get_def(&next))
+                {
+                    filled.insert(next);
+                    to_visit.push_back(next);
+                }
+            }
+        }
+        filled
+    }
+
+    /// split the points in the grid into cardinally-connected groups, where neighbours are grouped if `connected(a, b)` holds.
+    /// the index of a group in the result serves as its label
+    pub fn components(&self, connected: impl Fn(T, T) -> bool) -> Vec<HashSet<Pt<DIMS>>> {
+        let mut seen = HashSet::new();
+        let mut groups = vec![];
+
+        for (start, _) in self.grid.iter() {
+            if seen.contains(start) {
+                continue;
+            }
+            let mut group = HashSet::from([*start]);
+            let mut to_visit = vec![*start];
+
+            while let Some(pt) = to_visit.pop() {
+                let v = self.grid[&pt];
+                for off in &self.card_offsets {
+                    let next = &pt + off;
+                    if let Some(n) = self.grid.get(&next) {
+                        if !group.contains(&next) && connected(v, *n) {
+                            group.insert(next);
+                            to_visit.push(next);
+                        }
+                    }
+                }
+            }
+            seen.extend(group.iter().copied());
+            groups.push(group);
+        }
+        groups
+    }
+
+    /// find every point that can't be reached from outside the grid without crossing a boundary point
+    pub fn enclosed(&self, is_boundary: impl Fn(T) -> bool) -> HashSet<Pt<DIMS>> {
+        if self.grid.is_empty() {
+            return HashSet::new();
+        }
+        // pad the bounds so the outside is one connected space
+        let (mut mins, mut maxs) = self.bounds();
+        for i in 0..DIMS {
+            mins[i] -= 1;
+            maxs[i] += 1;
+        }
+        let outside = self.flood_within(Pt(mins), mins, maxs, |_, v| !is_boundary(v));
+
+        (0..DIMS)
+            .map(|i| mins[i]..maxs[i] + 1)
+            .multi_cartesian_product()
+            .map(|v| Pt(v.try_into().unwrap()))
+            .filter(|pt| !outside.contains(pt) && !is_boundary(self.get_def(pt)))
+            .collect()
+    }
+}
+
+/// the number of faces between a region and the space outside of it - the perimeter in 2D, surface area in 3D
+#[allow(dead_code)]
+pub fn perimeter<const DIMS: usize>(region: &HashSet<Pt<DIMS>>) -> usize {
+    let offsets = Pt::<DIMS>::card_offsets();
+    region
+        .iter()
+        .map(|pt| {
+            offsets
+                .iter()
+                .filter(|off| !region.contains(&(pt + off)))
+                .count()
+        })
+        .sum()
+}
+
+/// the number of straight sides of a 2D region, including the sides of any holes.
+/// a polygon has as many sides as it has corners, so we count those
+#[allow(dead_code)]
+pub fn sides(region: &HashSet<Pt<2>>) -> usize {
+    let turns = [
+        (Pt([0, -1]), Pt([1, 0])),
+        (Pt([1, 0]), Pt([0, 1])),
+        (Pt([0, 1]), Pt([-1, 0])),
+        (Pt([-1, 0]), Pt([0, -1])),
+    ];
+    region
+        .iter()
+        .map(|pt| {
+            turns
+                .iter()
+                .filter(|(a, b)| {
+                    let in_a = region.contains(&(pt + a));
+                    let in_b = region.contains(&(pt + b));
+                    let in_diag = region.contains(&(*pt + *a + *b));
+                    // outer corner, or inner corner
+                    (!in_a && !in_b) || (in_a && in_b && !in_diag)
+                })
+                .count()
+        })
+        .sum()
+}
+
+/// the conventional name for an axis: x, y, z, w, then d4, d5...
+fn axis_name(axis: usize) -> String {
+    match axis {
+        0 => "x".to_string(),
+        1 => "y".to_string(),
+        2 => "z".to_string(),
+        3 => "w".to_string(),
+        _ => format!("d{}", axis),
+    }
 }
 
+#[allow(dead_code)]
 impl<T: Copy> Grid<T, 2> {
+    /// rotate the grid 90 degrees clockwise (as printed), keeping the same top-left corner
+    pub fn rotate_90(self) -> Self {
+        if self.grid.is_empty() {
+            return self;
+        }
+        let ([min_x, min_y], [_, max_y]) = self.bounds();
+        self.remap(|Pt([x, y])| Pt([min_x + (max_y - y), min_y + (x - min_x)]))
+    }
+
+    /// rotate the grid 180 degrees, keeping the same top-left corner
+    pub fn rotate_180(self) -> Self {
+        self.flip(0).flip(1)
+    }
+
+    /// rotate the grid 270 degrees clockwise (as printed), keeping the same top-left corner
+    pub fn rotate_270(self) -> Self {
+        if self.grid.is_empty() {
+            return self;
+        }
+        let ([min_x, min_y], [max_x, _]) = self.bounds();
+        self.remap(|Pt([x, y])| Pt([min_x + (y - min_y), min_y + (max_x - x)]))
+    }
+
+    /// swap the x and y axes, keeping the same top-left corner
+    pub fn transpose(self) -> Self {
+        if self.grid.is_empty() {
+            return self;
+        }
+        let ([min_x, min_y], _) = self.bounds();
+        self.remap(|Pt([x, y])| Pt([min_x + (y - min_y), min_y + (x - min_x)]))
+    }
+
     /// print a 2d grid using a given function for representing points
     pub fn print(&self, to_printable: fn(T) -> char) -> String {
-        let mut res = String::from("\n");
+        let (mins, maxs) = self.bounds();
+        self.print_within(mins, maxs, to_printable)
+    }
 
-        let ([min_x, min_y], [max_x, max_y]) = self.bounds();
+    /// print the section of a 2d grid within the given (inclusive) bounds
+    fn print_within(
+        &self,
+        [min_x, min_y]: [isize; 2],
+        [max_x, max_y]: [isize; 2],
+        to_printable: fn(T) -> char,
+    ) -> String {
+        let mut res = String::from("\n");
 
         for y in min_y..max_y + 1 {
             for x in min_x..max_x + 1 {
@@ -129,8 +464,12 @@ impl<T: Copy> Grid<T, 2> {
 
 #[cfg(test)]
 mod tests {
-    use super::Grid;
-    use crate::utils::point::Pt;
+    use std::collections::HashSet;
+
+    use super::{perimeter, sides, Grid};
+    use crate::utils::{load_input::load_2d_grid, point::Pt};
+    use itertools::Itertools;
+    use rstest::rstest;
 
     #[test]
     fn test_transform() {
@@ -193,6 +532,167 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    fn digit_grid() -> Grid<u32, 2> {
+        Grid::<u32, 2>::from(vec![
+            (Pt([0, 0]), 1),
+            (Pt([1, 0]), 2),
+            (Pt([2, 0]), 3),
+            (Pt([0, 1]), 4),
+            (Pt([1, 1]), 5),
+            (Pt([2, 1]), 6),
+        ])
+    }
+
+    fn print_digits(grid: &Grid<u32, 2>) -> String {
+        grid.print(|x| char::from_digit(x, 10).unwrap_or('.'))
+    }
+
+    #[rstest]
+    #[case(|g: Grid<u32, 2>| g.rotate_90(), "\n41\n52\n63\n")]
+    #[case(|g: Grid<u32, 2>| g.rotate_180(), "\n654\n321\n")]
+    #[case(|g: Grid<u32, 2>| g.rotate_270(), "\n36\n25\n14\n")]
+    #[case(|g: Grid<u32, 2>| g.flip(0), "\n321\n654\n")]
+    #[case(|g: Grid<u32, 2>| g.flip(1), "\n456\n123\n")]
+    #[case(|g: Grid<u32, 2>| g.transpose(), "\n14\n25\n36\n")]
+    #[case(|g: Grid<u32, 2>| g.rotate_90().rotate_270(), "\n123\n456\n")]
+    #[case(|g: Grid<u32, 2>| g.crop([1, 0], [2, 1]), "\n23\n56\n")]
+    #[case(|g: Grid<u32, 2>| g.tile([2, 2]), "\n123123\n456456\n123123\n456456\n")]
+    fn test_geometric_transforms(
+        #[case] op: fn(Grid<u32, 2>) -> Grid<u32, 2>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, print_digits(&op(digit_grid())));
+    }
+
+    #[test]
+    fn test_rotation_keeps_corner() {
+        let grid = digit_grid().transform(|pt| pt + Pt([-5, 10]));
+
+        assert_eq!(([-5, 10], [-4, 12]), grid.clone().rotate_90().bounds());
+        assert_eq!(
+            print_digits(&digit_grid().rotate_90()),
+            print_digits(&grid.rotate_90())
+        );
+    }
+
+    #[test]
+    fn test_normalise() {
+        let grid = digit_grid().transform(|pt| pt + Pt([-5, 10])).normalise();
+
+        assert_eq!(digit_grid(), grid);
+    }
+
+    fn cube() -> Grid<u32, 3> {
+        // a 2x2x2 cube, with each point's value set to its position in reading order
+        Grid::<u32, 3>::from(
+            (0..8)
+                .map(|i| (Pt([i & 1, (i >> 1) & 1, i >> 2]), i as u32 + 1))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_slice() {
+        let grid = cube();
+
+        assert_eq!(
+            "\n12\n34\n",
+            print_digits(&grid.slice([0, 1], &Pt([0, 0, 0])))
+        );
+        assert_eq!(
+            "\n56\n78\n",
+            print_digits(&grid.slice([0, 1], &Pt([9, 9, 1])))
+        );
+        assert_eq!(
+            "\n15\n37\n",
+            print_digits(&grid.slice([2, 1], &Pt([0, 0, 0])))
+        );
+    }
+
+    #[test]
+    fn test_project() {
+        let grid = cube();
+
+        assert_eq!("\n56\n78\n", print_digits(&grid.project([0, 1], u32::max)));
+        assert_eq!("\n12\n56\n", print_digits(&grid.project([0, 2], u32::min)));
+    }
+
+    #[test]
+    fn test_print_nd() {
+        let mut grid = cube();
+        grid.grid.insert(Pt([2, 0, 1]), 9);
+
+        assert_eq!(
+            "\nz=0\n120\n340\n\nz=1\n569\n780\n",
+            grid.print_nd(|x| char::from_digit(x, 10).unwrap_or('.'))
+        );
+
+        let flat = digit_grid();
+        assert_eq!(
+            print_digits(&flat),
+            flat.print_nd(|x| char::from_digit(x, 10).unwrap_or('.'))
+        );
+
+        let hyper = Grid::<u32, 4>::from(vec![(Pt([0, 0, 0, 0]), 1), (Pt([0, 0, 1, 1]), 2)]);
+        assert_eq!(
+            "\nz=0, w=0\n1\n\nz=0, w=1\n0\n\nz=1, w=0\n0\n\nz=1, w=1\n2\n",
+            hyper.print_nd(|x| char::from_digit(x, 10).unwrap_or('.'))
+        );
+    }
+
+    #[rstest]
+    #[case("AAAA\nBBCD\nBBCC\nEEEC", vec![(1, 4, 4), (3, 8, 4), (4, 8, 4), (4, 10, 4), (4, 10, 8)])]
+    #[case("OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO", vec![(1, 4, 4), (1, 4, 4), (1, 4, 4), (1, 4, 4), (21, 36, 20)])]
+    fn test_components(#[case] input: &str, #[case] expected: Vec<(usize, usize, usize)>) {
+        let grid = load_2d_grid(input, |c| c);
+
+        let result = grid
+            .components(|a, b| a == b)
+            .iter()
+            .map(|region| (region.len(), perimeter(region), sides(region)))
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = load_2d_grid("#####\n#..#.\n#.##.\n#####", |c| c);
+
+        let result = grid.flood_fill(Pt([1, 1]), |_, c| c == '.');
+
+        assert_eq!(HashSet::from([Pt([1, 1]), Pt([2, 1]), Pt([1, 2])]), result);
+    }
+
+    #[rstest]
+    #[case("#####\n#...#\n#.#.#\n#####", 5)]
+    #[case("###\n#.#\n#..", 0)]
+    #[case("..#..\n.#.#.\n#...#\n.#.#.\n..#..", 5)]
+    fn test_enclosed(#[case] input: &str, #[case] expected: usize) {
+        let grid = load_2d_grid(input, |c| c);
+
+        assert_eq!(expected, grid.enclosed(|c| c == '#').len());
+    }
+
+    #[test]
+    fn test_enclosed_sparse() {
+        // a 3D shell around a single point, with nothing else stored in the grid
+        let shell = Pt::<3>::card_offsets()
+            .into_iter()
+            .map(|pt| (pt, true))
+            .collect_vec();
+        let mut grid = Grid::<bool, 3>::from(shell);
+        grid.grid.extend(
+            Pt::<3>::neighbour_offsets()
+                .into_iter()
+                .filter(|pt| pt.mag() == 2)
+                .map(|pt| (pt, true)),
+        );
+
+        assert_eq!(HashSet::from([Pt([0, 0, 0])]), grid.enclosed(|b| b));
+    }
+
     #[test]
     fn test_linear_iter() {
         let expected = vec![&Pt([0, 0]), &Pt([1, 0]), &Pt([0, 1]), &Pt([1, 1])];
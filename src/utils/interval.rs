@@ -0,0 +1,298 @@
+//! Ranges of numbers, and sets and boxes built from them, for when there are too many values to handle one at a time
+
+use std::ops::{Add, Mul, Sub};
+
+/// what an interval's endpoints need to support. `Default` is taken to be zero
+pub trait Endpoint: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>> Endpoint for T {}
+
+/// The half-open range of values `start..end`. Empty if `start >= end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+#[allow(dead_code)]
+impl<T: Endpoint> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    /// the interval covering `len` values from `start`
+    pub fn from_len(start: T, len: T) -> Self {
+        Self::new(start, start + len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// the number of values in the interval
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::default()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        self.start <= v && v < self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// the values in both intervals. may be empty
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// the values below `at`, and the values from `at` upwards. either may be empty
+    pub fn split(&self, at: T) -> (Self, Self) {
+        let at = at.clamp(self.start, self.end.max(self.start));
+        (Self::new(self.start, at), Self::new(at, self.end))
+    }
+
+    pub fn shift(&self, by: T) -> Self {
+        Self::new(self.start + by, self.end + by)
+    }
+}
+
+/// A set of values stored as the intervals covering them.
+/// Kept normalised: the intervals are sorted, non-empty, and never overlap or touch
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+#[allow(dead_code)]
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    /// sort and merge a list of intervals into a normalised set
+    fn normalise(mut intervals: Vec<Interval<T>>) -> Self {
+        intervals.retain(|i| !i.is_empty());
+        intervals.sort();
+
+        let mut merged: Vec<Interval<T>> = vec![];
+        for i in intervals {
+            match merged.last_mut() {
+                Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+                _ => merged.push(i),
+            }
+        }
+        Self { intervals: merged }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// the number of values in the set
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::default(), |acc, i| acc + i.len())
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        self.intervals.iter().any(|i| i.contains(v))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+        *self = Self::normalise(intervals);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalise(
+            self.intervals
+                .iter()
+                .chain(&other.intervals)
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        // both are sorted, so walk them together, stepping past whichever interval finishes first
+        let (mut a, mut b) = (0, 0);
+        let mut res = vec![];
+        while a < self.intervals.len() && b < other.intervals.len() {
+            let (x, y) = (self.intervals[a], other.intervals[b]);
+            res.push(x.intersection(&y));
+            if x.end < y.end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+        Self::normalise(res)
+    }
+
+    /// the values in self but not in other
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut res = vec![];
+        for i in &self.intervals {
+            let mut rest = *i;
+            for o in other.intervals.iter().filter(|o| o.overlaps(i)) {
+                let (below, _) = rest.split(o.start);
+                res.push(below);
+                rest = rest.split(o.end).1;
+            }
+            res.push(rest);
+        }
+        Self::normalise(res)
+    }
+
+    /// move every value in the set by the same amount
+    pub fn shift(&self, by: T) -> Self {
+        Self {
+            intervals: self.intervals.iter().map(|i| i.shift(by)).collect(),
+        }
+    }
+}
+
+impl<T: Endpoint> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        Self::normalise(vec![interval])
+    }
+}
+
+impl<T: Endpoint> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self::normalise(iter.into_iter().collect())
+    }
+}
+
+/// An N-dimensional box, made of an interval along each axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<T, const DIMS: usize>(pub [Interval<T>; DIMS]);
+
+#[allow(dead_code)]
+impl<T: Endpoint, const DIMS: usize> Cuboid<T, DIMS> {
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().any(|i| i.is_empty())
+    }
+
+    pub fn contains(&self, pt: &[T; DIMS]) -> bool {
+        self.0.iter().zip(pt).all(|(i, v)| i.contains(*v))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut res = *self;
+        for (i, o) in res.0.iter_mut().zip(&other.0) {
+            *i = i.intersection(o);
+        }
+        res
+    }
+
+    /// cut the box in two along an axis: the part below `at`, and the part from `at` upwards
+    pub fn split(&self, axis: usize, at: T) -> (Self, Self) {
+        let (below, above) = self.0[axis].split(at);
+        let (mut lo, mut hi) = (*self, *self);
+        lo.0[axis] = below;
+        hi.0[axis] = above;
+        (lo, hi)
+    }
+
+    /// the number of points in the box
+    pub fn volume(&self) -> T
+    where
+        T: Mul<Output = T>,
+    {
+        if self.is_empty() {
+            return T::default();
+        }
+        self.0
+            .iter()
+            .map(|i| i.len())
+            .reduce(|acc, l| acc * l)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cuboid, Interval, IntervalSet};
+    use rstest::rstest;
+
+    fn set(intervals: &[(isize, isize)]) -> IntervalSet<isize> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(10, (Interval::new(0, 5), Interval::new(5, 5)))]
+    #[case(3, (Interval::new(0, 3), Interval::new(3, 5)))]
+    #[case(-1, (Interval::new(0, 0), Interval::new(0, 5)))]
+    fn validate_split(#[case] at: isize, #[case] expected: (Interval<isize>, Interval<isize>)) {
+        assert_eq!(expected, Interval::new(0, 5).split(at));
+    }
+
+    #[test]
+    fn validate_normalise() {
+        let s = set(&[(5, 8), (0, 2), (2, 3), (7, 10), (12, 12)]);
+
+        assert_eq!(&[Interval::new(0, 3), Interval::new(5, 10)], s.intervals());
+        assert_eq!(8, s.len());
+        assert_eq!(Some(0), s.min());
+        assert!(s.contains(9));
+        assert!(!s.contains(3));
+    }
+
+    #[rstest]
+    #[case(&[(0, 5)], &[(3, 8)], &[(0, 8)], &[(3, 5)], &[(0, 3)])]
+    #[case(&[(0, 2), (4, 6)], &[(2, 4)], &[(0, 6)], &[], &[(0, 2), (4, 6)])]
+    #[case(&[(0, 10)], &[(2, 3), (5, 7)], &[(0, 10)], &[(2, 3), (5, 7)], &[(0, 2), (3, 5), (7, 10)])]
+    #[case(&[(0, 3), (5, 9)], &[(1, 6), (8, 20)], &[(0, 20)], &[(1, 3), (5, 6), (8, 9)], &[(0, 1), (6, 8)])]
+    #[case(&[], &[(1, 2)], &[(1, 2)], &[], &[])]
+    fn validate_set_ops(
+        #[case] a: &[(isize, isize)],
+        #[case] b: &[(isize, isize)],
+        #[case] union: &[(isize, isize)],
+        #[case] intersection: &[(isize, isize)],
+        #[case] difference: &[(isize, isize)],
+    ) {
+        let (a, b) = (set(a), set(b));
+
+        assert_eq!(set(union), a.union(&b));
+        assert_eq!(set(intersection), a.intersection(&b));
+        assert_eq!(set(difference), a.difference(&b));
+    }
+
+    #[test]
+    fn validate_cuboid() {
+        let c = Cuboid([Interval::new(1, 4001); 4]);
+        let (lo, hi) = c.split(0, 1351);
+
+        assert_eq!(4000_isize.pow(4), c.volume());
+        assert_eq!(c.volume(), lo.volume() + hi.volume());
+        assert!(lo.contains(&[1350, 1, 1, 4000]));
+        assert!(!lo.contains(&[1351, 1, 1, 4000]));
+        assert_eq!(0, lo.intersection(&hi).volume());
+        assert_eq!(
+            Cuboid([Interval::new(1, 3), Interval::new(2, 5)]).volume(),
+            Cuboid([Interval::new(0, 3), Interval::new(2, 9)])
+                .intersection(&Cuboid([Interval::new(1, 8), Interval::new(-4, 5)]))
+                .volume()
+        );
+    }
+}
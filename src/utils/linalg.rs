@@ -0,0 +1,285 @@
+//! Small dense linear systems, solved exactly with `Rational`s so big puzzle coordinates don't lose precision
+
+use std::ops::{Index, IndexMut, Mul};
+
+use super::maths::Rational;
+
+pub type Vector = Vec<Rational>;
+
+/// A dense matrix of rationals, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<Rational>,
+}
+
+#[allow(dead_code)]
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![Rational::ZERO; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = Rational::ONE;
+        }
+        m
+    }
+
+    /// build a matrix from its rows, which must all be the same length
+    pub fn from_rows<T: Into<Rational> + Copy>(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(
+            rows.iter().all(|r| r.len() == cols),
+            "rows must all be the same length"
+        );
+        Self {
+            rows: rows.len(),
+            cols,
+            data: rows.iter().flatten().map(|v| (*v).into()).collect(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, r: usize) -> &[Rational] {
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::zeros(self.cols, self.rows);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                t[(c, r)] = self[(r, c)];
+            }
+        }
+        t
+    }
+
+    pub fn mul_vec(&self, v: &[Rational]) -> Vector {
+        assert_eq!(self.cols, v.len(), "vector is the wrong length");
+        (0..self.rows).map(|r| dot(self.row(r), v)).collect()
+    }
+
+    /// add a column on the right hand side, for solving `self * x = b`
+    fn augment(&self, b: &[Rational]) -> Self {
+        assert_eq!(self.rows, b.len(), "right hand side is the wrong length");
+        let mut aug = Self::zeros(self.rows, self.cols + 1);
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                aug[(r, c)] = self[(r, c)];
+            }
+            aug[(r, self.cols)] = b[r];
+        }
+        aug
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for c in 0..self.cols {
+            self.data.swap(a * self.cols + c, b * self.cols + c);
+        }
+    }
+
+    /// Gauss-Jordan elimination into reduced row echelon form, returning the pivot column of each non-zero row
+    fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = vec![];
+        for c in 0..self.cols {
+            let r = pivots.len();
+            if r == self.rows {
+                break;
+            }
+            let Some(p) = (r..self.rows).find(|i| !self[(*i, c)].is_zero()) else {
+                continue;
+            };
+            self.swap_rows(r, p);
+
+            let scale = self[(r, c)].recip();
+            for j in c..self.cols {
+                self[(r, j)] = self[(r, j)] * scale;
+            }
+            for i in (0..self.rows).filter(|i| *i != r) {
+                let factor = self[(i, c)];
+                if factor.is_zero() {
+                    continue;
+                }
+                for j in c..self.cols {
+                    self[(i, j)] = self[(i, j)] - factor * self[(r, j)];
+                }
+            }
+            pivots.push(c);
+        }
+        pivots
+    }
+
+    /// the number of linearly independent rows
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    pub fn determinant(&self) -> Rational {
+        assert_eq!(self.rows, self.cols, "determinant of a non-square matrix");
+        let mut m = self.clone();
+        let mut det = Rational::ONE;
+        for c in 0..m.cols {
+            let Some(p) = (c..m.rows).find(|i| !m[(*i, c)].is_zero()) else {
+                return Rational::ZERO;
+            };
+            if p != c {
+                m.swap_rows(c, p);
+                det = -det;
+            }
+            let pivot = m[(c, c)];
+            det = det * pivot;
+            for i in c + 1..m.rows {
+                let factor = m[(i, c)] / pivot;
+                for j in c..m.cols {
+                    m[(i, j)] = m[(i, j)] - factor * m[(c, j)];
+                }
+            }
+        }
+        det
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = Rational;
+
+    fn index(&self, (r, c): (usize, usize)) -> &Self::Output {
+        &self.data[r * self.cols + c]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut Self::Output {
+        &mut self.data[r * self.cols + c]
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.cols, rhs.rows, "matrices can't be multiplied");
+        let rhs_t = rhs.transpose();
+        let mut m = Matrix::zeros(self.rows, rhs.cols);
+        for r in 0..self.rows {
+            for c in 0..rhs.cols {
+                m[(r, c)] = dot(self.row(r), rhs_t.row(c));
+            }
+        }
+        m
+    }
+}
+
+fn dot(a: &[Rational], b: &[Rational]) -> Rational {
+    a.iter()
+        .zip(b)
+        .fold(Rational::ZERO, |acc, (x, y)| acc + *x * *y)
+}
+
+/// solve `a * x = b` exactly. None if there's no solution, or infinitely many
+#[allow(dead_code)]
+pub fn solve(a: &Matrix, b: &[Rational]) -> Option<Vector> {
+    let mut aug = a.augment(b);
+    let pivots = aug.row_reduce();
+
+    // a pivot in the right hand side column means 0 = 1 somewhere
+    if pivots.len() < a.cols || pivots.contains(&a.cols) {
+        return None;
+    }
+    Some((0..a.cols).map(|r| aug[(r, a.cols)]).collect())
+}
+
+/// the x that minimises `|a * x - b|`, for when there are more equations than unknowns and they may not quite agree.
+/// solved exactly through the normal equations, so keep the coefficients modest. None if the columns of a aren't independent
+#[allow(dead_code)]
+pub fn least_squares(a: &Matrix, b: &[Rational]) -> Option<Vector> {
+    let a_t = a.transpose();
+    solve(&(&a_t * a), &a_t.mul_vec(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{least_squares, solve, Matrix, Vector};
+    use crate::utils::maths::Rational;
+    use rstest::rstest;
+
+    fn vector(vs: &[i128]) -> Vector {
+        vs.iter().map(|v| Rational::from(*v)).collect()
+    }
+
+    fn matrix(rows: &[Vec<i128>]) -> Matrix {
+        Matrix::from_rows(rows)
+    }
+
+    #[rstest]
+    // x + y = 3, x - y = 1
+    #[case(vec![vec![1, 1], vec![1, -1]], vec![3, 1], Some(vector(&[2, 1])))]
+    // needs a row swap, and has a fractional answer
+    #[case(vec![vec![0, 2], vec![3, 0]], vec![1, 1], Some(vec![Rational::new(1, 3), Rational::new(1, 2)]))]
+    #[case(vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]], vec![8, -11, -3], Some(vector(&[2, 3, -1])))]
+    // the same line twice
+    #[case(vec![vec![1, 1], vec![2, 2]], vec![1, 2], None)]
+    // parallel lines
+    #[case(vec![vec![1, 1], vec![1, 1]], vec![1, 2], None)]
+    // more equations than unknowns, but they agree
+    #[case(vec![vec![1, 0], vec![0, 1], vec![1, 1]], vec![4, 5, 9], Some(vector(&[4, 5])))]
+    fn validate_solve(
+        #[case] a: Vec<Vec<i128>>,
+        #[case] b: Vec<i128>,
+        #[case] expected: Option<Vector>,
+    ) {
+        assert_eq!(expected, solve(&matrix(&a), &vector(&b)));
+    }
+
+    #[test]
+    fn validate_big_solve() {
+        // the kind of sizes day 24 throws around
+        let x = vector(&[287_430_900_705_823, 451_620_998_712_421, -97, 131]);
+        let a = matrix(&[
+            vec![3, -2, 120_000_000_000_000, 97],
+            vec![-5, 7, 3, -280_000_000_000_000],
+            vec![11, 1, -99_999_999_999_999, 5],
+            vec![2, -13, 17, 200_000_000_000_001],
+        ]);
+
+        assert_eq!(Some(x.clone()), solve(&a, &a.mul_vec(&x)));
+    }
+
+    #[test]
+    fn validate_least_squares() {
+        // fit y = m * x + c through points that aren't quite on a line
+        let a = matrix(&[vec![0, 1], vec![1, 1], vec![2, 1], vec![3, 1]]);
+        let b = vector(&[1, 2, 4, 4]);
+
+        assert_eq!(
+            Some(vec![Rational::new(11, 10), Rational::new(11, 10)]),
+            least_squares(&a, &b)
+        );
+    }
+
+    #[test]
+    fn validate_matrix() {
+        let a = matrix(&[vec![1, 2], vec![3, 4]]);
+        let b = matrix(&[vec![0, 1], vec![1, 0]]);
+
+        assert_eq!(matrix(&[vec![2, 1], vec![4, 3]]), &a * &b);
+        assert_eq!(matrix(&[vec![1, 3], vec![2, 4]]), a.transpose());
+        assert_eq!(a, &a * &Matrix::identity(2));
+        assert_eq!(Rational::from(-2i128), a.determinant());
+        assert_eq!(2, a.rank());
+        assert_eq!(1, matrix(&[vec![1, 2], vec![2, 4]]).rank());
+    }
+}
@@ -1,20 +1,72 @@
-use super::grid::Grid;
+//! Helper functions for loading common input styles
 
-/**
-Helper functions for loading common input styles
-*/
+use std::collections::HashMap;
+
+use super::{grid::Grid, point::Pt};
 
 /// Load a string representation of a 2D grid into a Grid object
 /// NOTE: We always assume the top-left elemnt is 0,0, and that x increases rightward and y increases downward
 pub fn load_2d_grid<T: Copy + Default>(input: &str, to_value: fn(char) -> T) -> Grid<T, 2> {
+    load_2d_grid_with(input, to_value, &GridOptions::default()).0
+}
+
+/// Options for `load_2d_grid_with`. The defaults behave the same as `load_2d_grid`
+#[derive(Default, Clone)]
+pub struct GridOptions<'a, T> {
+    /// characters that shouldn't be stored in the grid, e.g. background '.'s, so the grid stays sparse
+    pub ignore: &'a str,
+    /// characters whose locations should be returned, e.g. the 'S' start point
+    pub symbols: &'a str,
+    /// make y increase upward, so the bottom row is on the origin's y
+    pub flip_y: bool,
+    /// the coordinate of the top-left character (or bottom-left, if y is flipped)
+    pub origin: Pt<2>,
+    /// add a border of (width, value) around the input's bounds
+    pub padding: Option<(usize, T)>,
+}
+
+/// Load a string representation of a 2D grid into a Grid object, with some control over how it's done.
+/// Also returns the locations of each of `options.symbols`, in reading order
+pub fn load_2d_grid_with<T: Copy + Default>(
+    input: &str,
+    to_value: fn(char) -> T,
+    options: &GridOptions<T>,
+) -> (Grid<T, 2>, HashMap<char, Vec<Pt<2>>>) {
+    let lines: Vec<&str> = input.lines().collect();
+    let height = lines.len() as isize;
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as isize;
+    let to_pt = |x: isize, y: isize| {
+        let y = if options.flip_y { height - 1 - y } else { y };
+        options.origin + Pt([x, y])
+    };
+
     let mut pairs = vec![];
-    for (y, line) in (0..).zip(input.split('\n')) {
+    let mut symbols: HashMap<char, Vec<Pt<2>>> = HashMap::new();
+
+    for (y, line) in (0..).zip(&lines) {
         for (x, c) in (0..).zip(line.chars()) {
-            pairs.push((vec![x, y], to_value(c)));
+            let pt = to_pt(x, y);
+            if options.symbols.contains(c) {
+                symbols.entry(c).or_default().push(pt);
+            }
+            if !options.ignore.contains(c) {
+                pairs.push((pt, to_value(c)));
+            }
+        }
+    }
+
+    if let Some((border, value)) = options.padding {
+        let border = border as isize;
+        for y in -border..height + border {
+            for x in -border..width + border {
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    pairs.push((to_pt(x, y), value));
+                }
+            }
         }
     }
 
-    Grid::from(pairs)
+    (Grid::from(pairs), symbols)
 }
 
 /// load values from an \n-seperated list
@@ -36,7 +88,13 @@ pub fn load_segmented_lines<T>(
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::{grid::Grid, load_input::load_2d_grid, point::Pt};
+    use std::{collections::HashMap, convert::identity};
+
+    use crate::utils::{
+        grid::Grid,
+        load_input::{load_2d_grid, load_2d_grid_with, GridOptions},
+        point::Pt,
+    };
 
     #[test]
     fn test_load_2d_grid() {
@@ -63,4 +121,49 @@ mod tests {
 
         assert_eq!(expected.grid, result.grid);
     }
+
+    #[test]
+    fn test_load_2d_grid_with_options() {
+        let input = "S.#\n..#\n";
+
+        let (grid, symbols) = load_2d_grid_with(
+            input,
+            identity,
+            &GridOptions {
+                ignore: ".",
+                symbols: "S#",
+                flip_y: true,
+                origin: Pt([10, 20]),
+                padding: None,
+            },
+        );
+
+        let expected = Grid::<char, 2>::from(vec![
+            (Pt([10, 21]), 'S'),
+            (Pt([12, 21]), '#'),
+            (Pt([12, 20]), '#'),
+        ]);
+        let expected_symbols = HashMap::from([
+            ('S', vec![Pt([10, 21])]),
+            ('#', vec![Pt([12, 21]), Pt([12, 20])]),
+        ]);
+
+        assert_eq!(expected.grid, grid.grid);
+        assert_eq!(expected_symbols, symbols);
+    }
+
+    #[test]
+    fn test_load_2d_grid_with_padding() {
+        let (grid, _) = load_2d_grid_with(
+            "ab\ncd",
+            identity,
+            &GridOptions {
+                padding: Some((1, '#')),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(([-1, -1], [2, 2]), grid.bounds());
+        assert_eq!("\n####\n#ab#\n#cd#\n####\n", grid.print(identity));
+    }
 }
@@ -0,0 +1,311 @@
+//! Longest simple paths through mazes, by shrinking the maze down to its junctions first
+
+use std::collections::HashMap;
+
+use super::{grid::Grid, point::Pt};
+
+/// A maze contracted down to the points where paths meet, joined by the length of the corridors between them.
+/// Edges are one-way, so mazes with one-way steps still work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JunctionGraph {
+    pub nodes: Vec<Pt<2>>,
+    /// for each node, the nodes it leads to and how far away they are
+    pub edges: Vec<Vec<(usize, usize)>>,
+}
+
+#[allow(dead_code)]
+impl JunctionGraph {
+    /// contract a grid, where `moves` gives the points that can be stepped to from a point.
+    /// the junctions are every point with more than two moves, plus the `ends` we want to keep regardless
+    pub fn from_grid<T: Default + Copy>(
+        grid: &Grid<T, 2>,
+        ends: &[Pt<2>],
+        moves: impl Fn(&Pt<2>, T) -> Vec<Pt<2>>,
+    ) -> Self {
+        let step = |p: &Pt<2>| moves(p, grid.get_def(p));
+        let mut nodes = grid
+            .grid
+            .keys()
+            .filter(|p| ends.contains(p) || step(p).len() > 2)
+            .copied()
+            .collect::<Vec<_>>();
+        nodes.sort();
+        let index: HashMap<Pt<2>, usize> = nodes.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+        let edges = nodes
+            .iter()
+            .map(|j| {
+                // follow each corridor out of the junction until it reaches another one, keeping the longest way to each
+                let mut reached: HashMap<usize, usize> = HashMap::new();
+                for first in step(j) {
+                    let (mut prev, mut at, mut dist) = (*j, first, 1);
+                    while !index.contains_key(&at) {
+                        let next = step(&at)
+                            .into_iter()
+                            .filter(|n| *n != prev)
+                            .collect::<Vec<_>>();
+                        if next.len() != 1 {
+                            break;
+                        }
+                        (prev, at, dist) = (at, next[0], dist + 1);
+                    }
+                    if let Some(to) = index.get(&at).filter(|to| **to != index[j]) {
+                        let d = reached.entry(*to).or_default();
+                        *d = dist.max(*d);
+                    }
+                }
+                let mut out = reached.into_iter().collect::<Vec<_>>();
+                out.sort();
+                out
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    pub fn index_of(&self, pt: &Pt<2>) -> Option<usize> {
+        self.nodes.iter().position(|n| n == pt)
+    }
+
+    /// the longest path between two points, with its junctions given as points
+    pub fn longest_path(&self, start: &Pt<2>, end: &Pt<2>) -> Option<LongestPath<Pt<2>>> {
+        let found = longest_simple_path(&self.edges, self.index_of(start)?, self.index_of(end)?)?;
+        Some(LongestPath {
+            length: found.length,
+            path: found.path.iter().map(|i| self.nodes[*i]).collect(),
+            explored: found.explored,
+        })
+    }
+}
+
+/// The result of a longest path search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongestPath<N> {
+    pub length: usize,
+    /// every node along the way, from the start to the end
+    pub path: Vec<N>,
+    /// how many partial paths were looked at to find it
+    pub explored: usize,
+}
+
+struct Search<'a> {
+    edges: &'a [Vec<(usize, usize)>],
+    end: usize,
+    /// the heaviest edge into each node - the most a node could ever add to a path
+    best_in: Vec<usize>,
+    /// if the end can only be reached from one node, reaching that node means going straight to the end
+    last_step: Option<(usize, usize)>,
+    path: Vec<usize>,
+    best: Option<(usize, Vec<usize>)>,
+    explored: usize,
+}
+
+impl Search<'_> {
+    fn dfs(&mut self, at: usize, visited: u128, length: usize, potential: usize) {
+        self.explored += 1;
+        self.path.push(at);
+
+        if at == self.end {
+            if self.best.as_ref().map_or(true, |(b, _)| length > *b) {
+                self.best = Some((length, self.path.clone()));
+            }
+        } else if self
+            .best
+            .as_ref()
+            .map_or(true, |(b, _)| length + potential > *b)
+        {
+            let forced = self
+                .last_step
+                .filter(|(from, _)| *from == at)
+                .map(|(_, dist)| (self.end, dist));
+            let edges = self.edges;
+            for (to, dist) in forced.iter().chain(edges[at].iter()) {
+                if visited & (1 << to) == 0 {
+                    self.dfs(
+                        *to,
+                        visited | (1 << to),
+                        length + dist,
+                        potential - self.best_in[*to],
+                    );
+                }
+                if forced.is_some() {
+                    break;
+                }
+            }
+        }
+
+        self.path.pop();
+    }
+}
+
+/// the longest path from start to end that doesn't visit any node twice, as a list of nodes.
+/// `edges` gives, for each node, the nodes it leads to and how far. this is NP-hard in general, so is an exhaustive
+/// depth first search, pruned by how much the unvisited nodes could possibly add. handles up to 128 nodes.
+/// None if the end can't be reached
+#[allow(dead_code)]
+pub fn longest_simple_path(
+    edges: &[Vec<(usize, usize)>],
+    start: usize,
+    end: usize,
+) -> Option<LongestPath<usize>> {
+    assert!(edges.len() <= 128, "too many nodes for a 128 bit mask");
+
+    let mut best_in = vec![0; edges.len()];
+    let mut into_end = vec![];
+    for (from, out) in edges.iter().enumerate() {
+        for (to, dist) in out {
+            best_in[*to] = best_in[*to].max(*dist);
+            if *to == end {
+                into_end.push((from, *dist));
+            }
+        }
+    }
+    let last_step = match into_end[..] {
+        [only] => Some(only),
+        _ => None,
+    };
+
+    let mut search = Search {
+        edges,
+        end,
+        best_in: best_in.clone(),
+        last_step,
+        path: vec![],
+        best: None,
+        explored: 0,
+    };
+    let potential = best_in.iter().sum::<usize>() - best_in[start];
+    search.dfs(start, 1 << start, 0, potential);
+
+    let (length, path) = search.best?;
+    Some(LongestPath {
+        length,
+        path,
+        explored: search.explored,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{longest_simple_path, JunctionGraph};
+    use crate::utils::{grid::Grid, point::Pt};
+    use rstest::rstest;
+
+    /// both directions of each edge
+    fn undirected(n: usize, edges: &[(usize, usize, usize)]) -> Vec<Vec<(usize, usize)>> {
+        let mut out = vec![vec![]; n];
+        for (a, b, d) in edges {
+            out[*a].push((*b, *d));
+            out[*b].push((*a, *d));
+        }
+        out
+    }
+
+    /// every simple path, with no pruning at all
+    fn brute_force(
+        edges: &[Vec<(usize, usize)>],
+        at: usize,
+        end: usize,
+        seen: &mut Vec<bool>,
+    ) -> Option<usize> {
+        if at == end {
+            return Some(0);
+        }
+        seen[at] = true;
+        let mut best = None;
+        for (to, d) in &edges[at] {
+            if !seen[*to] {
+                if let Some(l) = brute_force(edges, *to, end, seen) {
+                    best = best.max(Some(l + d));
+                }
+            }
+        }
+        seen[at] = false;
+        best
+    }
+
+    #[rstest]
+    // the direct route is short, but the detour is long
+    #[case(undirected(4, &[(0, 3, 1), (0, 1, 5), (1, 2, 5), (2, 3, 5)]), Some((15, vec![0, 1, 2, 3])))]
+    // taking the greedy first step (0 -> 1) rules out the best path
+    #[case(undirected(4, &[(0, 1, 10), (0, 2, 1), (2, 1, 1), (1, 3, 1), (2, 3, 20)]), Some((31, vec![0, 1, 2, 3])))]
+    // one way only
+    #[case(vec![vec![(1, 2)], vec![(2, 2)], vec![(0, 9)], vec![]], None)]
+    #[case(vec![vec![]], Some((0, vec![0])))]
+    fn validate_longest_simple_path(
+        #[case] edges: Vec<Vec<(usize, usize)>>,
+        #[case] expected: Option<(usize, Vec<usize>)>,
+    ) {
+        let end = edges.len() - 1;
+        let found = longest_simple_path(&edges, 0, end).map(|f| (f.length, f.path));
+
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn validate_pruning() {
+        // a lattice of junctions, the shape day 23's mazes have (though theirs are 6x6)
+        let size = 5;
+        let mut edges = vec![];
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * size + x;
+                if x + 1 < size {
+                    edges.push((i, i + 1, 1 + (i * 7) % 5));
+                }
+                if y + 1 < size {
+                    edges.push((i, i + size, 1 + (i * 3) % 4));
+                }
+            }
+        }
+        let graph = undirected(size * size, &edges);
+        let found = longest_simple_path(&graph, 0, size * size - 1).unwrap();
+
+        assert_eq!(
+            brute_force(&graph, 0, size * size - 1, &mut vec![false; size * size]),
+            Some(found.length)
+        );
+        assert!(found.path.len() <= size * size);
+        assert_eq!(Some(&0), found.path.first());
+        assert_eq!(Some(&(size * size - 1)), found.path.last());
+        assert!(found.explored > 0);
+    }
+
+    #[test]
+    fn validate_junction_graph() {
+        let maze = "#.#####
+#.....#
+#.###.#
+#.....#
+###.###
+###...#
+#####.#";
+        let mut grid = Grid::<bool, 2>::default();
+        for (y, l) in maze.lines().enumerate() {
+            for (x, c) in l.chars().enumerate() {
+                if c == '.' {
+                    grid.grid.insert(Pt([x as isize, y as isize]), true);
+                }
+            }
+        }
+        let (start, end) = (Pt([1, 0]), Pt([5, 6]));
+        let dirs = [Pt([0, 1]), Pt([-1, 0]), Pt([1, 0]), Pt([0, -1])];
+        let junctions = JunctionGraph::from_grid(&grid, &[start, end], |p, _| {
+            dirs.iter()
+                .map(|d| *p + *d)
+                .filter(|n| grid.get_def(n))
+                .collect()
+        });
+
+        // the two ends, and the two points where the loop branches
+        assert_eq!(
+            vec![Pt([1, 0]), Pt([1, 1]), Pt([3, 3]), Pt([5, 6])],
+            junctions.nodes
+        );
+
+        let found = junctions.longest_path(&start, &end).unwrap();
+        assert_eq!(vec![start, Pt([1, 1]), Pt([3, 3]), end], found.path);
+        // around the long side of the loop
+        assert_eq!(1 + 8 + 5, found.length);
+    }
+}
@@ -1,15 +1,593 @@
-use std::mem::swap;
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use super::point::Pt;
 
 pub fn gcd(mut a: u128, mut b: u128) -> u128 {
     while b != 0 {
-        if b < a {
-            swap(&mut a, &mut b);
-        }
-        b %= a;
+        (a, b) = (b, a % b);
     }
     a
 }
 
+/// divides before multiplying, so this only overflows if the result does
 pub fn lcm(a: u128, b: u128) -> u128 {
-    (a * b) / gcd(a, b)
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    a / gcd(a, b) * b
+}
+
+/// the lowest common multiple of every value, or None if it doesn't fit in a u128
+#[allow(dead_code)]
+pub fn lcm_all(values: impl IntoIterator<Item = u128>) -> Option<u128> {
+    values.into_iter().try_fold(1, |acc: u128, v| {
+        if acc == 0 || v == 0 {
+            return Some(0);
+        }
+        (acc / gcd(acc, v)).checked_mul(v)
+    })
+}
+
+/// find (g, x, y) where g is the gcd of a and b, and a*x + b*y = g
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// find x in 0..m where a*x = 1 (mod m), if there is one
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == 1 {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+/// Chinese Remainder Theorem: find the smallest non-negative x where x = residue (mod modulus) for every pair,
+/// returned along with the lcm of the moduli (which the solution repeats on).
+/// the moduli don't need to be coprime. None if the congruences contradict each other
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(a1, m1), (a2, m2)| {
+            let (g, p, _) = extended_gcd(m1, m2);
+            let diff = a2 - a1;
+            if diff % g != 0 {
+                return None;
+            }
+            // solve m1 * k = diff (mod m2)
+            let m2_g = m2 / g;
+            let k = ((diff / g) % m2_g * (p % m2_g)).rem_euclid(m2_g);
+            let modulus = m1.checked_mul(m2_g)?;
+            Some(((a1 + m1 * k).rem_euclid(modulus), modulus))
+        })
+}
+
+/// A repeating event, which happens at `offset`, then every `period` steps after that
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: i128,
+    pub period: i128,
+}
+
+#[allow(dead_code)]
+impl Cycle {
+    pub fn new(offset: i128, period: i128) -> Self {
+        Self { offset, period }
+    }
+
+    /// does the event happen at step t?
+    pub fn hits(&self, t: i128) -> bool {
+        t >= self.offset && (t - self.offset) % self.period == 0
+    }
+
+    /// the cycle of steps where both events happen at once, or None if they never line up
+    pub fn combine(&self, other: &Cycle) -> Option<Cycle> {
+        let (x, period) = crt([
+            (self.offset.rem_euclid(self.period), self.period),
+            (other.offset.rem_euclid(other.period), other.period),
+        ])?;
+        // the first time both have started, that's on both cycles
+        let start = self.offset.max(other.offset);
+        let offset = if x >= start {
+            x
+        } else {
+            x + (start - x + period - 1) / period * period
+        };
+        Some(Cycle { offset, period })
+    }
+}
+
+/// the first step where every event happens at once, or None if they never line up
+#[allow(dead_code)]
+pub fn align_cycles(cycles: impl IntoIterator<Item = Cycle>) -> Option<i128> {
+    let mut cycles = cycles.into_iter();
+    let first = cycles.next()?;
+    cycles
+        .try_fold(first, |acc, c| acc.combine(&c))
+        .map(|c| c.offset)
+}
+
+fn checked(v: Option<i128>) -> i128 {
+    v.expect("overflowed i128 during exact arithmetic")
+}
+
+/// An exact fraction, always kept in its lowest terms with a positive denominator.
+/// Backed by i128, which fits the product of two of the ~1e15 coordinates AoC likes to throw at us, but not much more:
+/// eliminating a whole system of equations with coefficients that size can outgrow it, so shrink them first.
+/// Overflow panics rather than silently giving the wrong answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+#[allow(dead_code)]
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "rational with a denominator of 0");
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+        let sign = den.signum();
+        Self {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -(-*self).floor()
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.den, self.num)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(v: i128) -> Self {
+        Self { num: v, den: 1 }
+    }
+}
+
+impl From<isize> for Rational {
+    fn from(v: isize) -> Self {
+        Self::from(v as i128)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        // scale by the gcd of the denominators, not their product, to keep the numbers small
+        let g = gcd(self.den.unsigned_abs(), rhs.den.unsigned_abs()) as i128;
+        let num = checked(
+            checked(self.num.checked_mul(rhs.den / g))
+                .checked_add(checked(rhs.num.checked_mul(self.den / g))),
+        );
+        Self::new(num, checked((self.den / g).checked_mul(rhs.den)))
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        // cancel before multiplying, to keep the numbers small
+        let g1 = gcd(self.num.unsigned_abs(), rhs.den.unsigned_abs()).max(1) as i128;
+        let g2 = gcd(rhs.num.unsigned_abs(), self.den.unsigned_abs()).max(1) as i128;
+        Self::new(
+            checked((self.num / g1).checked_mul(rhs.num / g2)),
+            checked((self.den / g2).checked_mul(rhs.den / g1)),
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.recip()
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive, so cross-multiplying keeps the order
+        checked(self.num.checked_mul(other.den)).cmp(&checked(other.num.checked_mul(self.den)))
+    }
+}
+
+/// Where two lines `p1 + t * d1` and `p2 + u * d2` cross
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intersection {
+    pub t: Rational,
+    pub u: Rational,
+    pub at: [Rational; 2],
+}
+
+/// find exactly where two 2D lines cross. None if they're parallel (or the same line)
+#[allow(dead_code)]
+pub fn line_intersection(p1: Pt<2>, d1: Pt<2>, p2: Pt<2>, d2: Pt<2>) -> Option<Intersection> {
+    let cross = |Pt([ax, ay]): Pt<2>, Pt([bx, by]): Pt<2>| {
+        checked((ax as i128).checked_mul(by as i128))
+            - checked((ay as i128).checked_mul(bx as i128))
+    };
+
+    let denom = cross(d1, d2);
+    if denom == 0 {
+        return None;
+    }
+    let between = p2 - p1;
+    let t = Rational::new(cross(between, d2), denom);
+    let u = Rational::new(cross(between, d1), denom);
+
+    Some(Intersection {
+        t,
+        u,
+        at: [
+            Rational::from(p1.0[0]) + t * Rational::from(d1.0[0]),
+            Rational::from(p1.0[1]) + t * Rational::from(d1.0[1]),
+        ],
+    })
+}
+
+/// find exactly where two 2D rays (lines that only go forward from their start point) cross
+#[allow(dead_code)]
+pub fn ray_intersection(p1: Pt<2>, d1: Pt<2>, p2: Pt<2>, d2: Pt<2>) -> Option<Intersection> {
+    line_intersection(p1, d1, p2, d2).filter(|i| i.t >= Rational::ZERO && i.u >= Rational::ZERO)
+}
+
+/// the value at x of the lowest degree polynomial passing through every point, by Lagrange's formula.
+/// the xs must all be different
+#[allow(dead_code)]
+pub fn lagrange(points: &[(Rational, Rational)], x: Rational) -> Rational {
+    points
+        .iter()
+        .enumerate()
+        .fold(Rational::ZERO, |acc, (i, (xi, yi))| {
+            let basis = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Rational::ONE, |b, (_, (xj, _))| b * (x - *xj) / (*xi - *xj));
+            acc + *yi * basis
+        })
+}
+
+/// The lowest degree polynomial through a set of points, in Newton's divided difference form.
+/// Cheaper than `lagrange` when evaluating at lots of x
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Newton {
+    xs: Vec<Rational>,
+    coefs: Vec<Rational>,
+}
+
+#[allow(dead_code)]
+impl Newton {
+    /// the xs must all be different
+    pub fn fit(points: &[(Rational, Rational)]) -> Self {
+        let xs = points.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+        let mut diffs = points.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+        let mut coefs = vec![];
+
+        for level in 0..points.len() {
+            coefs.push(diffs[0]);
+            diffs = diffs
+                .windows(2)
+                .enumerate()
+                .map(|(i, w)| (w[1] - w[0]) / (xs[i + level + 1] - xs[i]))
+                .collect();
+        }
+        Self { xs, coefs }
+    }
+
+    pub fn eval(&self, x: Rational) -> Rational {
+        // Horner's method, from the highest order term down
+        self.coefs
+            .iter()
+            .zip(&self.xs)
+            .rev()
+            .fold(Rational::ZERO, |acc, (c, xi)| acc * (x - *xi) + *c)
+    }
+}
+
+/// the value at index `at` of the polynomial sequence starting `seq[0], seq[1], ...`, found from its finite differences.
+/// `at` can be past the end to extrapolate forwards, or negative to extrapolate backwards
+#[allow(dead_code)]
+pub fn extrapolate(seq: &[i128], at: i128) -> i128 {
+    // the first value of each row of differences
+    let mut leading = vec![];
+    let mut diffs = seq.to_vec();
+    while diffs.iter().any(|v| *v != 0) {
+        leading.push(diffs[0]);
+        diffs = diffs
+            .windows(2)
+            .map(|w| checked(w[1].checked_sub(w[0])))
+            .collect();
+    }
+
+    // f(at) = sum of choose(at, k) * leading[k], where choose works for any integer at
+    let mut choose: i128 = 1;
+    let mut total: i128 = 0;
+    for (k, d) in leading.iter().enumerate() {
+        total = checked(total.checked_add(checked(choose.checked_mul(*d))));
+        choose = checked(choose.checked_mul(at - k as i128)) / (k as i128 + 1);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        align_cycles, crt, extended_gcd, extrapolate, gcd, lagrange, lcm, lcm_all,
+        line_intersection, mod_inverse, ray_intersection, Cycle, Newton, Rational,
+    };
+    use crate::utils::point::Pt;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(12, 18, 6, 36)]
+    #[case(0, 5, 5, 0)]
+    #[case(7, 0, 7, 0)]
+    #[case(17, 13, 1, 221)]
+    fn validate_gcd_lcm(#[case] a: u128, #[case] b: u128, #[case] g: u128, #[case] l: u128) {
+        assert_eq!(g, gcd(a, b));
+        assert_eq!(g, gcd(b, a));
+        assert_eq!(l, lcm(a, b));
+    }
+
+    #[test]
+    fn validate_lcm_all() {
+        assert_eq!(Some(60), lcm_all([2, 3, 4, 5, 6]));
+        assert_eq!(Some(1), lcm_all([]));
+        // a*b alone would overflow here, but the lcm fits
+        assert_eq!(Some(u128::MAX / 3), lcm_all([u128::MAX / 3, u128::MAX / 3]));
+        assert_eq!(None, lcm_all([1 << 127, 3]));
+    }
+
+    #[rstest]
+    #[case(240, 46, 2)]
+    #[case(-240, 46, 2)]
+    #[case(17, 5, 1)]
+    #[case(0, 9, 9)]
+    fn validate_extended_gcd(#[case] a: i128, #[case] b: i128, #[case] expected: i128) {
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(expected, g);
+        assert_eq!(g, a * x + b * y);
+    }
+
+    #[rstest]
+    #[case(3, 11, Some(4))]
+    #[case(10, 17, Some(12))]
+    #[case(-3, 11, Some(7))]
+    #[case(6, 9, None)]
+    fn validate_mod_inverse(#[case] a: i128, #[case] m: i128, #[case] expected: Option<i128>) {
+        assert_eq!(expected, mod_inverse(a, m));
+    }
+
+    #[rstest]
+    #[case(vec![(2, 3), (3, 5), (2, 7)], Some((23, 105)))]
+    #[case(vec![(0, 3), (3, 4), (4, 5)], Some((39, 60)))]
+    // not coprime, but consistent
+    #[case(vec![(3, 4), (5, 6)], Some((11, 12)))]
+    // not coprime, and inconsistent
+    #[case(vec![(1, 4), (2, 6)], None)]
+    #[case(vec![], Some((0, 1)))]
+    fn validate_crt(
+        #[case] congruences: Vec<(i128, i128)>,
+        #[case] expected: Option<(i128, i128)>,
+    ) {
+        assert_eq!(expected, crt(congruences));
+    }
+
+    #[rstest]
+    // cycles starting at 0 are just the lcm
+    #[case(vec![Cycle::new(2, 2), Cycle::new(3, 3)], Some(6))]
+    // an offset shifts where they meet
+    #[case(vec![Cycle::new(3, 4), Cycle::new(5, 6)], Some(11))]
+    // they meet at 1, but the second hasn't started yet
+    #[case(vec![Cycle::new(1, 3), Cycle::new(13, 4)], Some(13))]
+    #[case(vec![Cycle::new(1, 3), Cycle::new(14, 4)], Some(22))]
+    #[case(vec![Cycle::new(0, 2), Cycle::new(1, 4)], None)]
+    #[case(vec![Cycle::new(5, 7)], Some(5))]
+    fn validate_align_cycles(#[case] cycles: Vec<Cycle>, #[case] expected: Option<i128>) {
+        let result = align_cycles(cycles.clone());
+        assert_eq!(expected, result);
+        if let Some(t) = result {
+            assert!(cycles.iter().all(|c| c.hits(t)));
+            assert!((0..t).all(|earlier| !cycles.iter().all(|c| c.hits(earlier))));
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 3, 6, 9, 12, 15], 6, 18)]
+    #[case(vec![1, 3, 6, 10, 15, 21], 6, 28)]
+    #[case(vec![10, 13, 16, 21, 30, 45], 6, 68)]
+    #[case(vec![10, 13, 16, 21, 30, 45], -1, 5)]
+    #[case(vec![10, 13, 16, 21, 30, 45], 2, 16)]
+    // squares, a long way either side
+    #[case(vec![0, 1, 4], 202_300, 202_300 * 202_300)]
+    #[case(vec![0, 1, 4], -1_000, 1_000_000)]
+    #[case(vec![7], 100, 7)]
+    #[case(vec![0, 0, 0], 5, 0)]
+    fn validate_extrapolate(#[case] seq: Vec<i128>, #[case] at: i128, #[case] expected: i128) {
+        assert_eq!(expected, extrapolate(&seq, at));
+    }
+
+    #[test]
+    fn validate_interpolation() {
+        // y = x^2 / 2 - 3, at uneven xs
+        let f = |x: Rational| x * x / Rational::from(2i128) - Rational::from(3i128);
+        let points = [-2i128, 1, 5]
+            .map(|x| (Rational::from(x), f(Rational::from(x))))
+            .to_vec();
+        let newton = Newton::fit(&points);
+
+        for x in [
+            Rational::from(-7i128),
+            Rational::new(1, 3),
+            Rational::from(100i128),
+        ] {
+            assert_eq!(f(x), lagrange(&points, x));
+            assert_eq!(f(x), newton.eval(x));
+        }
+    }
+
+    #[test]
+    fn validate_rational() {
+        let half = Rational::new(2, 4);
+        let third = Rational::new(-1, -3);
+
+        assert_eq!((1, 2), (half.num(), half.den()));
+        assert_eq!((1, 3), (third.num(), third.den()));
+        assert_eq!(Rational::new(5, 6), half + third);
+        assert_eq!(Rational::new(1, 6), half - third);
+        assert_eq!(Rational::new(1, 6), half * third);
+        assert_eq!(Rational::new(3, 2), half / third);
+        assert_eq!(Rational::new(-1, 2), -half);
+        assert!(third < half);
+        assert!(-half < third);
+        assert_eq!("-7/2", Rational::new(7, -2).to_string());
+        assert_eq!("4", Rational::new(8, 2).to_string());
+    }
+
+    #[rstest]
+    #[case(Rational::new(7, 2), 3, 4)]
+    #[case(Rational::new(-7, 2), -4, -3)]
+    #[case(Rational::from(5_i128), 5, 5)]
+    fn validate_rounding(#[case] r: Rational, #[case] floor: i128, #[case] ceil: i128) {
+        assert_eq!(floor, r.floor());
+        assert_eq!(ceil, r.ceil());
+    }
+
+    #[test]
+    fn validate_line_intersection() {
+        // y = x and y = 2 - x cross at (1, 1)
+        let i = line_intersection(Pt([0, 0]), Pt([1, 1]), Pt([0, 2]), Pt([1, -1])).unwrap();
+        assert_eq!([Rational::ONE, Rational::ONE], i.at);
+        assert_eq!((Rational::ONE, Rational::ONE), (i.t, i.u));
+
+        // y = x / 3 and y = 1 - x cross at a fractional point, (3/4, 1/4)
+        let i = line_intersection(Pt([0, 0]), Pt([3, 1]), Pt([0, 1]), Pt([1, -1])).unwrap();
+        assert_eq!([Rational::new(3, 4), Rational::new(1, 4)], i.at);
+        assert_eq!((Rational::new(1, 4), Rational::new(3, 4)), (i.t, i.u));
+
+        let i = line_intersection(Pt([0, 0]), Pt([2, 0]), Pt([1, 1]), Pt([0, 3])).unwrap();
+        assert_eq!([Rational::ONE, Rational::ZERO], i.at);
+        assert_eq!((Rational::new(1, 2), Rational::new(-1, 3)), (i.t, i.u));
+
+        assert_eq!(
+            None,
+            line_intersection(Pt([0, 0]), Pt([1, 1]), Pt([0, 2]), Pt([-2, -2]))
+        );
+    }
+
+    #[test]
+    fn validate_ray_intersection() {
+        // the crossing point is behind the second ray
+        assert_eq!(
+            None,
+            ray_intersection(Pt([0, 0]), Pt([2, 0]), Pt([1, 1]), Pt([0, 3]))
+        );
+        assert!(ray_intersection(Pt([0, 0]), Pt([2, 0]), Pt([1, 1]), Pt([0, -3])).is_some());
+
+        // large coordinates, like AoC 2023 day 24, where f64 maths loses precision
+        let i = ray_intersection(
+            Pt([300_000_000_000_001, 200_000_000_000_000]),
+            Pt([-1, 3]),
+            Pt([200_000_000_000_000, 300_000_000_000_002]),
+            Pt([1, 1]),
+        )
+        .unwrap();
+        assert_eq!(Rational::new(1_000_000_000_000_001, 4), i.at[0]);
+    }
 }
@@ -0,0 +1,138 @@
+//! A cache for recursive solvers, which keeps count of how often it saves us any work
+
+use std::{collections::HashMap, fmt::Display, hash::Hash};
+
+/// How well a `Memo` has been doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+#[allow(dead_code)]
+impl MemoStats {
+    /// the fraction of lookups that were already cached
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            0.0
+        } else {
+            self.hits as f64 / (self.hits + self.misses) as f64
+        }
+    }
+}
+
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} entries",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.entries
+        )
+    }
+}
+
+/// Remembers the result for each key it's asked about.
+/// Keys can borrow from the puzzle input - `(usize, &[usize])` rather than `(usize, Vec<usize>)` - so nothing needs cloning to look it up
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: MemoStats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: HashMap::new(),
+            stats: MemoStats::default(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the cached value for a key, or else work it out and cache it.
+    /// `compute` is given the memo back, so it can recurse through it
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return v.clone();
+        }
+        self.stats.misses += 1;
+
+        let v = compute(self);
+        self.cache.insert(key, v.clone());
+        v
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            entries: self.cache.len(),
+            ..self.stats
+        }
+    }
+
+    /// forget everything, including the stats
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = MemoStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Memo;
+    use rstest::rstest;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fib(memo, n - 1) + fib(memo, n - 2)
+            }
+        })
+    }
+
+    /// the number of ways to make `total` from the coins, borrowing the coins as the key
+    fn change<'a>(memo: &mut Memo<(u64, &'a [u64]), u64>, total: u64, coins: &'a [u64]) -> u64 {
+        memo.get_or_compute((total, coins), |memo| match coins.split_first() {
+            _ if total == 0 => 1,
+            None => 0,
+            Some((c, rest)) => (0..=total / c)
+                .map(|n| change(memo, total - n * c, rest))
+                .sum(),
+        })
+    }
+
+    #[test]
+    fn validate_fib() {
+        let mut memo = Memo::new();
+
+        assert_eq!(12_586_269_025, fib(&mut memo, 50));
+        let stats = memo.stats();
+        // each n is worked out once, then looked up once more by n + 2 - apart from 0, which 1 doesn't need
+        assert_eq!((51, 48, 51), (stats.misses, stats.hits, stats.entries));
+
+        memo.clear();
+        assert_eq!(0, memo.stats().entries);
+    }
+
+    #[rstest]
+    #[case(100, &[50, 25, 10, 5, 1], 292)]
+    #[case(10, &[5, 2], 2)]
+    #[case(3, &[2], 0)]
+    fn validate_borrowed_keys(#[case] total: u64, #[case] coins: &[u64], #[case] expected: u64) {
+        let mut memo = Memo::new();
+
+        assert_eq!(expected, change(&mut memo, total, coins));
+        assert!(memo.stats().misses > 0);
+    }
+}
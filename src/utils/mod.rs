@@ -1,5 +1,15 @@
+pub mod bitgrid;
+pub mod circuit;
+pub mod cycle;
+pub mod geometry;
+pub mod graph;
 pub mod grid;
+pub mod interval;
+pub mod linalg;
 pub mod load_input;
+pub mod longest_path;
 pub mod maths;
+pub mod memo;
 pub mod point;
 pub mod solver_types;
+pub mod workflow;
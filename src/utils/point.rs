@@ -1,8 +1,10 @@
 use std::{
     collections::HashSet,
-    ops::{Add, AddAssign, Mul, Neg, Sub},
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Sub, SubAssign},
+    str::FromStr,
 };
 
+use anyhow::anyhow;
 use itertools::Itertools;
 
 #[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
@@ -76,6 +78,12 @@ impl<const DIMS: usize> AddAssign for Pt<DIMS> {
     }
 }
 
+impl<const DIMS: usize> SubAssign for Pt<DIMS> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 impl<const DIMS: usize> Mul<isize> for Pt<DIMS> {
     type Output = Pt<DIMS>;
 
@@ -87,6 +95,75 @@ impl<const DIMS: usize> Mul<isize> for Pt<DIMS> {
     }
 }
 
+impl<const DIMS: usize> MulAssign<isize> for Pt<DIMS> {
+    fn mul_assign(&mut self, rhs: isize) {
+        *self = *self * rhs;
+    }
+}
+
+/// component-wise multiplication
+impl<const DIMS: usize> Mul for Pt<DIMS> {
+    type Output = Pt<DIMS>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a * b)
+    }
+}
+
+/// component-wise division, rounding toward zero
+impl<const DIMS: usize> Div for Pt<DIMS> {
+    type Output = Pt<DIMS>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a / b)
+    }
+}
+
+/// component-wise remainder. see `rem_euclid` for wrapping negative coordinates
+impl<const DIMS: usize> Rem for Pt<DIMS> {
+    type Output = Pt<DIMS>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a % b)
+    }
+}
+
+impl<const DIMS: usize> From<[isize; DIMS]> for Pt<DIMS> {
+    fn from(arr: [isize; DIMS]) -> Self {
+        Pt(arr)
+    }
+}
+
+impl<const DIMS: usize> From<Pt<DIMS>> for [isize; DIMS] {
+    fn from(pt: Pt<DIMS>) -> Self {
+        pt.0
+    }
+}
+
+impl From<(isize, isize)> for Pt<2> {
+    fn from((x, y): (isize, isize)) -> Self {
+        Pt([x, y])
+    }
+}
+
+impl From<Pt<2>> for (isize, isize) {
+    fn from(Pt([x, y]): Pt<2>) -> Self {
+        (x, y)
+    }
+}
+
+impl From<(isize, isize, isize)> for Pt<3> {
+    fn from((x, y, z): (isize, isize, isize)) -> Self {
+        Pt([x, y, z])
+    }
+}
+
+impl From<Pt<3>> for (isize, isize, isize) {
+    fn from(Pt([x, y, z]): Pt<3>) -> Self {
+        (x, y, z)
+    }
+}
+
 #[allow(dead_code)]
 impl<const DIMS: usize> Pt<DIMS> {
     /// get all the offsets required to get every neighbour to a position
@@ -115,13 +192,81 @@ impl<const DIMS: usize> Pt<DIMS> {
     pub fn mag(&self) -> isize {
         self.0.iter().map(|v| v.abs()).sum()
     }
+
+    /// apply a function to each component
+    pub fn map(mut self, f: impl Fn(isize) -> isize) -> Self {
+        for v in &mut self.0 {
+            *v = f(*v);
+        }
+        self
+    }
+
+    /// combine each component with the matching component of another point
+    pub fn zip_with(mut self, rhs: Self, f: impl Fn(isize, isize) -> isize) -> Self {
+        for (i, v) in self.0.iter_mut().enumerate() {
+            *v = f(*v, rhs.0[i]);
+        }
+        self
+    }
+
+    /// wrap each component into 0..rhs, e.g. for finding where a point lands on a repeating map
+    pub fn rem_euclid(self, rhs: Self) -> Self {
+        self.zip_with(rhs, isize::rem_euclid)
+    }
+
+    pub fn abs(self) -> Self {
+        self.map(isize::abs)
+    }
+
+    pub fn signum(self) -> Self {
+        self.map(isize::signum)
+    }
+
+    /// the smallest value of each component. (`min` would be the lexicographic minimum from `Ord`)
+    pub fn component_min(self, rhs: Self) -> Self {
+        self.zip_with(rhs, isize::min)
+    }
+
+    /// the largest value of each component
+    pub fn component_max(self, rhs: Self) -> Self {
+        self.zip_with(rhs, isize::max)
+    }
+
+    pub fn dot(&self, rhs: &Self) -> isize {
+        (0..DIMS).map(|i| self.0[i] * rhs.0[i]).sum()
+    }
+
+    /// the number of cardinal steps between two points
+    pub fn manhattan(&self, rhs: &Self) -> isize {
+        (self - rhs).mag()
+    }
+
+    /// the number of steps between two points, if diagonal steps are allowed
+    pub fn chebyshev(&self, rhs: &Self) -> isize {
+        (self - rhs).0.iter().map(|v| v.abs()).max().unwrap_or(0)
+    }
+
+    /// the square of the straight-line distance between two points. no square root, so no floats
+    pub fn euclidean_sq(&self, rhs: &Self) -> isize {
+        let d = self - rhs;
+        d.dot(&d)
+    }
+}
+
+impl Pt<3> {
+    pub fn cross(&self, rhs: &Self) -> Self {
+        let Pt([a1, a2, a3]) = *self;
+        let Pt([b1, b2, b3]) = *rhs;
+        Pt([a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
-    use super::Pt;
+    use super::{Pt, D, D8};
+    use itertools::Itertools;
     use rstest::rstest;
 
     #[test]
@@ -212,7 +357,161 @@ mod tests {
     #[case(Pt([100,200,300]), Pt([1,2,3]), 100)]
     #[case(Pt([-100,-200,-300]), Pt([1,2,3]), -100)]
     fn validate_mul(#[case] expected: Pt<3>, #[case] a: Pt<3>, #[case] b: isize) {
-        assert_eq!(expected, a * b)
+        assert_eq!(expected, a * b);
+
+        let mut c = a;
+        c *= b;
+        assert_eq!(expected, c);
+    }
+
+    #[rstest]
+    #[case(Pt([3, -8]), Pt([7, -9]), Pt([4, -1]))]
+    #[case(Pt([0, 0]), Pt([0, 0]), Pt([0, 0]))]
+    fn validate_sub(#[case] expected: Pt<2>, #[case] a: Pt<2>, #[case] b: Pt<2>) {
+        assert_eq!(expected, a - b);
+
+        let mut c = a;
+        c -= b;
+        assert_eq!(expected, c);
+    }
+
+    #[rstest]
+    #[case(Pt([7, -9, 4]), Pt([2, 3, 5]), Pt([14, -27, 20]), Pt([3, -3, 0]), Pt([1, 0, 4]))]
+    #[case(Pt([-7, 9, 0]), Pt([2, 2, 5]), Pt([-14, 18, 0]), Pt([-3, 4, 0]), Pt([-1, 1, 0]))]
+    fn validate_componentwise(
+        #[case] a: Pt<3>,
+        #[case] b: Pt<3>,
+        #[case] product: Pt<3>,
+        #[case] quotient: Pt<3>,
+        #[case] remainder: Pt<3>,
+    ) {
+        assert_eq!(product, a * b);
+        assert_eq!(quotient, a / b);
+        assert_eq!(remainder, a % b);
+    }
+
+    #[rstest]
+    #[case(Pt([3, 4]), Pt([5, 5]), Pt([3, 4]))]
+    #[case(Pt([-1, -6]), Pt([5, 5]), Pt([4, 4]))]
+    #[case(Pt([-11, 11]), Pt([11, 11]), Pt([0, 0]))]
+    fn validate_rem_euclid(#[case] a: Pt<2>, #[case] b: Pt<2>, #[case] expected: Pt<2>) {
+        assert_eq!(expected, a.rem_euclid(b));
+    }
+
+    #[test]
+    fn validate_component_fns() {
+        let a = Pt([-3, 0, 5]);
+        let b = Pt([2, -1, 7]);
+
+        assert_eq!(Pt([3, 0, 5]), a.abs());
+        assert_eq!(Pt([-1, 0, 1]), a.signum());
+        assert_eq!(Pt([-3, -1, 5]), a.component_min(b));
+        assert_eq!(Pt([2, 0, 7]), a.component_max(b));
+        assert_eq!(Pt([-6, 0, 10]), a.map(|v| v * 2));
+    }
+
+    #[rstest]
+    #[case(Pt([1, 6]), Pt([5, 11]), 9, 5, 41)]
+    #[case(Pt([-2, -2]), Pt([2, 2]), 8, 4, 32)]
+    #[case(Pt([4, 0]), Pt([4, 0]), 0, 0, 0)]
+    fn validate_distances(
+        #[case] a: Pt<2>,
+        #[case] b: Pt<2>,
+        #[case] manhattan: isize,
+        #[case] chebyshev: isize,
+        #[case] euclidean_sq: isize,
+    ) {
+        assert_eq!(manhattan, a.manhattan(&b));
+        assert_eq!(manhattan, b.manhattan(&a));
+        assert_eq!(chebyshev, a.chebyshev(&b));
+        assert_eq!(euclidean_sq, a.euclidean_sq(&b));
+    }
+
+    #[test]
+    fn validate_products() {
+        let x = Pt([1, 0, 0]);
+        let y = Pt([0, 1, 0]);
+
+        assert_eq!(Pt([0, 0, 1]), x.cross(&y));
+        assert_eq!(Pt([0, 0, -1]), y.cross(&x));
+        assert_eq!(Pt([-3, 6, -3]), Pt([1, 2, 3]).cross(&Pt([4, 5, 6])));
+        assert_eq!(32, Pt([1, 2, 3]).dot(&Pt([4, 5, 6])));
+        assert_eq!(0, x.dot(&y));
+    }
+
+    #[rstest]
+    #[case(D::Up, D::Left, D::Right, D::Down)]
+    #[case(D::Right, D::Up, D::Down, D::Left)]
+    #[case(D::Down, D::Right, D::Left, D::Up)]
+    #[case(D::Left, D::Down, D::Up, D::Right)]
+    fn validate_turns(#[case] d: D, #[case] left: D, #[case] right: D, #[case] opposite: D) {
+        assert_eq!(left, d.turn_left());
+        assert_eq!(right, d.turn_right());
+        assert_eq!(opposite, d.opposite());
+        assert_eq!(d, d.turn_left().turn_right());
+        assert_eq!(-d.val(), opposite.val());
+        // turning right is clockwise as printed, i.e. the same as rotating the grid 90 degrees
+        assert_eq!(Pt([-d.val().0[1], d.val().0[0]]), right.val());
+    }
+
+    #[rstest]
+    #[case("U", D::Up)]
+    #[case("N", D::Up)]
+    #[case("^", D::Up)]
+    #[case("↓", D::Down)]
+    #[case("W", D::Left)]
+    #[case(">", D::Right)]
+    fn validate_parse_d(#[case] s: &str, #[case] expected: D) {
+        assert_eq!(expected, s.parse::<D>().unwrap());
+        assert_eq!(expected, D::try_from(expected.val()).unwrap());
+        assert_eq!(D8::from(expected), s.parse::<D8>().unwrap());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("UP")]
+    #[case("x")]
+    fn validate_parse_d_fails(#[case] s: &str) {
+        assert!(s.parse::<D>().is_err());
+    }
+
+    #[test]
+    fn validate_d8() {
+        assert_eq!(8, D8::iter().map(|d| d.val()).unique().count());
+        assert_eq!(
+            Pt::<2>::neighbour_offsets(),
+            D8::iter().map(|d| d.val()).collect()
+        );
+        assert_eq!(
+            Pt::<2>::card_offsets(),
+            D::iter().map(|d| d.val()).collect()
+        );
+
+        for d in D8::iter() {
+            assert_eq!(d, d.turn_left().turn_right());
+            assert_eq!(-d.val(), d.opposite().val());
+            assert_eq!(d, D8::try_from(d.val()).unwrap());
+            assert_eq!(d.is_diagonal(), d.val().mag() == 2);
+        }
+        assert_eq!(D8::UpRight, D8::Up.turn_right());
+        assert_eq!(D8::UpLeft, D8::Up.turn_left());
+        assert_eq!(D8::DownLeft, "SW".parse().unwrap());
+        assert!(D::try_from(Pt([1, 1])).is_err());
+        assert!(D8::try_from(Pt([2, 0])).is_err());
+    }
+
+    #[test]
+    fn validate_conversions() {
+        assert_eq!(Pt([1, 2]), Pt::from((1, 2)));
+        assert_eq!(Pt([1, 2, 3]), (1, 2, 3).into());
+        assert_eq!(Pt([1, 2, 3, 4]), [1, 2, 3, 4].into());
+
+        let (x, y): (isize, isize) = Pt([5, 6]).into();
+        assert_eq!((5, 6), (x, y));
+        let t: (isize, isize, isize) = Pt([5, 6, 7]).into();
+        assert_eq!((5, 6, 7), t);
+        let arr: [isize; 2] = Pt([8, 9]).into();
+        assert_eq!([8, 9], arr);
     }
 }
 
@@ -224,7 +523,11 @@ pub enum D {
     Right,
 }
 
+#[allow(dead_code)]
 impl D {
+    /// every direction, clockwise from Up
+    pub const ALL: [D; 4] = [D::Up, D::Right, D::Down, D::Left];
+
     pub fn val(&self) -> Pt<2> {
         match self {
             D::Up => Pt([0, -1]),
@@ -233,4 +536,182 @@ impl D {
             D::Right => Pt([1, 0]),
         }
     }
+
+    /// turn 90 degrees anticlockwise (as printed, with y increasing downward)
+    pub fn turn_left(&self) -> D {
+        match self {
+            D::Up => D::Left,
+            D::Left => D::Down,
+            D::Down => D::Right,
+            D::Right => D::Up,
+        }
+    }
+
+    /// turn 90 degrees clockwise (as printed, with y increasing downward)
+    pub fn turn_right(&self) -> D {
+        match self {
+            D::Up => D::Right,
+            D::Right => D::Down,
+            D::Down => D::Left,
+            D::Left => D::Up,
+        }
+    }
+
+    pub fn opposite(&self) -> D {
+        match self {
+            D::Up => D::Down,
+            D::Down => D::Up,
+            D::Left => D::Right,
+            D::Right => D::Left,
+        }
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, D::Up | D::Down)
+    }
+
+    pub fn iter() -> impl Iterator<Item = D> {
+        D::ALL.into_iter()
+    }
+}
+
+/// parse U/D/L/R, N/E/S/W or an arrow (^v<> or ↑↓←→)
+impl TryFrom<char> for D {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'U' | 'N' | '^' | '↑' => Ok(D::Up),
+            'D' | 'S' | 'v' | '↓' => Ok(D::Down),
+            'L' | 'W' | '<' | '←' => Ok(D::Left),
+            'R' | 'E' | '>' | '→' => Ok(D::Right),
+            _ => Err(anyhow!("'{}' is not a direction", c)),
+        }
+    }
+}
+
+impl FromStr for D {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.chars().exactly_one() {
+            Ok(c) => D::try_from(c),
+            Err(_) => Err(anyhow!("'{}' is not a direction", s)),
+        }
+    }
+}
+
+/// convert a unit offset into the direction it points in
+impl TryFrom<Pt<2>> for D {
+    type Error = anyhow::Error;
+
+    fn try_from(pt: Pt<2>) -> Result<Self, Self::Error> {
+        D::iter()
+            .find(|d| d.val() == pt)
+            .ok_or_else(|| anyhow!("{:?} is not a cardinal unit offset", pt))
+    }
+}
+
+/// Directions including diagonals
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum D8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+#[allow(dead_code)]
+impl D8 {
+    /// every direction, clockwise from Up
+    pub const ALL: [D8; 8] = [
+        D8::Up,
+        D8::UpRight,
+        D8::Right,
+        D8::DownRight,
+        D8::Down,
+        D8::DownLeft,
+        D8::Left,
+        D8::UpLeft,
+    ];
+
+    pub fn val(&self) -> Pt<2> {
+        match self {
+            D8::Up => Pt([0, -1]),
+            D8::UpRight => Pt([1, -1]),
+            D8::Right => Pt([1, 0]),
+            D8::DownRight => Pt([1, 1]),
+            D8::Down => Pt([0, 1]),
+            D8::DownLeft => Pt([-1, 1]),
+            D8::Left => Pt([-1, 0]),
+            D8::UpLeft => Pt([-1, -1]),
+        }
+    }
+
+    fn rotate(&self, eighths: usize) -> D8 {
+        D8::ALL[(*self as usize + eighths) % 8]
+    }
+
+    /// turn 45 degrees anticlockwise (as printed, with y increasing downward)
+    pub fn turn_left(&self) -> D8 {
+        self.rotate(7)
+    }
+
+    /// turn 45 degrees clockwise (as printed, with y increasing downward)
+    pub fn turn_right(&self) -> D8 {
+        self.rotate(1)
+    }
+
+    pub fn opposite(&self) -> D8 {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        (*self as usize) % 2 == 1
+    }
+
+    pub fn iter() -> impl Iterator<Item = D8> {
+        D8::ALL.into_iter()
+    }
+}
+
+impl From<D> for D8 {
+    fn from(d: D) -> Self {
+        match d {
+            D::Up => D8::Up,
+            D::Down => D8::Down,
+            D::Left => D8::Left,
+            D::Right => D8::Right,
+        }
+    }
+}
+
+/// parse anything `D` can, or a compass direction (N, NE, E...)
+impl FromStr for D8 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NE" => Ok(D8::UpRight),
+            "SE" => Ok(D8::DownRight),
+            "SW" => Ok(D8::DownLeft),
+            "NW" => Ok(D8::UpLeft),
+            _ => s.parse::<D>().map(D8::from),
+        }
+    }
+}
+
+/// convert a unit offset (including diagonals) into the direction it points in
+impl TryFrom<Pt<2>> for D8 {
+    type Error = anyhow::Error;
+
+    fn try_from(pt: Pt<2>) -> Result<Self, Self::Error> {
+        D8::iter()
+            .find(|d| d.val() == pt)
+            .ok_or_else(|| anyhow!("{:?} is not a unit offset", pt))
+    }
 }
@@ -0,0 +1,537 @@
+//! Sorting parts through networks of workflows, like day 19's, and working out which parts make it through
+
+use std::{cmp::Ordering, collections::HashMap, fmt::Display};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use super::interval::{Cuboid, Interval};
+
+/// the letters of the four ratings, in the order they're stored in a `Part`
+pub const CATEGORIES: [char; 4] = ['x', 'm', 'a', 's'];
+/// every part starts in this workflow
+pub const START: &str = "in";
+
+/// A part's ratings, in the order of `CATEGORIES`
+pub type Part = [usize; 4];
+/// A box of parts, with a range for each rating
+pub type Region = Cuboid<usize, 4>;
+
+/// every rating is between 1 and 4000
+pub fn all_parts() -> Region {
+    Cuboid([Interval::new(1, 4001); 4])
+}
+
+/// load a line like `{x=787,m=2655,a=1222,s=2876}`
+pub fn parse_part(line: &str) -> Result<Part> {
+    let inner = line
+        .trim()
+        .strip_prefix('{')
+        .and_then(|l| l.strip_suffix('}'))
+        .ok_or_else(|| anyhow!("expected a part in braces, got '{}'", line))?;
+    let mut part = [None; 4];
+    for rating in inner.split(',') {
+        let (c, v) = rating
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `category=rating`, got '{}'", rating))?;
+        part[category(c)?] = Some(v.parse()?);
+    }
+    let missing = |i: usize| anyhow!("part '{}' has no {} rating", line, CATEGORIES[i]);
+    Ok([
+        part[0].ok_or_else(|| missing(0))?,
+        part[1].ok_or_else(|| missing(1))?,
+        part[2].ok_or_else(|| missing(2))?,
+        part[3].ok_or_else(|| missing(3))?,
+    ])
+}
+
+fn category(c: &str) -> Result<usize> {
+    CATEGORIES
+        .iter()
+        .position(|x| c.len() == 1 && c.starts_with(*x))
+        .ok_or_else(|| anyhow!("unknown category '{}'", c))
+}
+
+/// A test on one rating, like `a<2006`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub category: usize,
+    /// `Less` for `<`, `Greater` for `>`
+    pub ord: Ordering,
+    pub value: usize,
+}
+
+#[allow(dead_code)]
+impl Condition {
+    pub fn matches(&self, part: &Part) -> bool {
+        part[self.category].cmp(&self.value) == self.ord
+    }
+
+    /// the parts of a region that match, and the parts that don't
+    pub fn split(&self, region: &Region) -> (Region, Region) {
+        match self.ord {
+            Ordering::Less => region.split(self.category, self.value),
+            _ => {
+                let (below, above) = region.split(self.category, self.value + 1);
+                (above, below)
+            }
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = if self.ord == Ordering::Less { '<' } else { '>' };
+        write!(f, "{}{}{}", CATEGORIES[self.category], op, self.value)
+    }
+}
+
+/// Where a rule sends a part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Accept,
+    Reject,
+    /// on to another workflow, by its index
+    Workflow(usize),
+}
+
+/// Sends a part to its target if it matches the condition. Rules without a condition match everything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub condition: Option<Condition>,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone)]
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<Rule>,
+}
+
+/// One rule that a part was checked against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub workflow: String,
+    /// the rule as it was written, like `a<2006:qkq`
+    pub rule: String,
+    pub matched: bool,
+}
+
+/// Every rule a part was checked against on its way through, and where it ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    pub accepted: bool,
+}
+
+#[allow(dead_code)]
+impl Explanation {
+    /// the workflows visited, like `in -> qqz -> qs -> lnx -> A`
+    pub fn route(&self) -> String {
+        self.steps
+            .iter()
+            .map(|s| s.workflow.as_str())
+            .dedup()
+            .chain([if self.accepted { "A" } else { "R" }])
+            .join(" -> ")
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for s in &self.steps {
+            let outcome = if s.matched { "matched" } else { "skipped" };
+            writeln!(f, "{}: {} {}", s.workflow, s.rule, outcome)?;
+        }
+        write!(
+            f,
+            "{}",
+            if self.accepted {
+                "accepted"
+            } else {
+                "rejected"
+            }
+        )
+    }
+}
+
+/// A checked set of workflows, with every target resolved to an index.
+/// Once parsed, every part is guaranteed to end up accepted or rejected
+#[derive(Debug, Clone)]
+pub struct Workflows {
+    workflows: Vec<Workflow>,
+    ids: HashMap<String, usize>,
+    start: usize,
+}
+
+#[allow(dead_code)]
+impl Workflows {
+    /// load lines like `px{a<2006:qkq,m>2090:A,rfg}`. fails if a rule sends parts to a workflow that doesn't exist,
+    /// if parts could go round in a loop or fall off the end of a workflow, or if there's a rule no part could ever reach
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut declared = vec![];
+        for l in input.lines().filter(|l| !l.trim().is_empty()) {
+            let (name, rules) = l
+                .trim()
+                .strip_suffix('}')
+                .and_then(|l| l.split_once('{'))
+                .ok_or_else(|| anyhow!("expected `name{{rules}}`, got '{}'", l))?;
+            declared.push((name, rules.split(',').collect::<Vec<_>>()));
+        }
+
+        let mut ids = HashMap::new();
+        for (i, (name, _)) in declared.iter().enumerate() {
+            if ids.insert(name.to_string(), i).is_some() {
+                return Err(anyhow!("workflow '{}' is declared twice", name));
+            }
+        }
+        let start = *ids
+            .get(START)
+            .ok_or_else(|| anyhow!("there's no '{}' workflow", START))?;
+
+        let workflows = declared
+            .iter()
+            .map(|(name, rules)| {
+                let rules = rules
+                    .iter()
+                    .map(|r| Self::parse_rule(&ids, name, r))
+                    .collect::<Result<_>>()?;
+                Ok(Workflow {
+                    name: name.to_string(),
+                    rules,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let workflows = Self {
+            workflows,
+            ids,
+            start,
+        };
+        workflows.validate()?;
+        Ok(workflows)
+    }
+
+    fn parse_rule(ids: &HashMap<String, usize>, workflow: &str, rule: &str) -> Result<Rule> {
+        let (condition, target) = match rule.split_once(':') {
+            Some((cond, target)) => {
+                let at = cond
+                    .find(['<', '>'])
+                    .ok_or_else(|| anyhow!("expected `<` or `>` in '{}'", rule))?;
+                let condition = Condition {
+                    category: category(&cond[..at])?,
+                    ord: if cond[at..].starts_with('<') {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    },
+                    value: cond[at + 1..].parse()?,
+                };
+                (Some(condition), target)
+            }
+            None => (None, rule),
+        };
+        let target = match target {
+            "A" => Target::Accept,
+            "R" => Target::Reject,
+            name => Target::Workflow(*ids.get(name).ok_or_else(|| {
+                anyhow!(
+                    "workflow '{}' sends parts to '{}', which doesn't exist",
+                    workflow,
+                    name
+                )
+            })?),
+        };
+        Ok(Rule { condition, target })
+    }
+
+    fn validate(&self) -> Result<()> {
+        for w in &self.workflows {
+            if w.rules.last().map_or(true, |r| r.condition.is_some()) {
+                return Err(anyhow!(
+                    "workflow '{}' has no rule for parts that match nothing else",
+                    w.name
+                ));
+            }
+        }
+
+        if let Some(cycle) = self.find_cycle() {
+            return Err(anyhow!(
+                "parts can go round in a loop: {}",
+                cycle.iter().map(|i| &self.workflows[*i].name).join(" -> ")
+            ));
+        }
+
+        let mut reached = self
+            .workflows
+            .iter()
+            .map(|w| vec![false; w.rules.len()])
+            .collect();
+        self.flow(self.start, all_parts(), &mut reached, &mut vec![]);
+        let unreachable = self
+            .workflows
+            .iter()
+            .zip(reached)
+            .flat_map(|(w, r)| {
+                w.rules
+                    .iter()
+                    .zip(r)
+                    .filter(|(_, r)| !r)
+                    .map(|(rule, _)| format!("{}: {}", w.name, self.describe(rule)))
+            })
+            .collect_vec();
+        if !unreachable.is_empty() {
+            return Err(anyhow!(
+                "no part can ever reach these rules: {}",
+                unreachable.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// a loop of workflows, starting and ending at the same one, if there is one
+    fn find_cycle(&self) -> Option<Vec<usize>> {
+        // 0 = not yet seen, 1 = on the current path, 2 = finished with
+        let mut state = vec![0; self.workflows.len()];
+        let mut path = vec![];
+
+        fn dfs(
+            workflows: &Workflows,
+            at: usize,
+            state: &mut [u8],
+            path: &mut Vec<usize>,
+        ) -> Option<Vec<usize>> {
+            state[at] = 1;
+            path.push(at);
+            for r in &workflows.workflows[at].rules {
+                if let Target::Workflow(to) = r.target {
+                    if state[to] == 1 {
+                        let from = path.iter().position(|p| *p == to).unwrap();
+                        return Some(path[from..].iter().copied().chain([to]).collect());
+                    }
+                    if state[to] == 0 {
+                        if let Some(cycle) = dfs(workflows, to, state, path) {
+                            return Some(cycle);
+                        }
+                    }
+                }
+            }
+            path.pop();
+            state[at] = 2;
+            None
+        }
+
+        (0..self.workflows.len()).find_map(|i| {
+            if state[i] == 0 {
+                dfs(self, i, &mut state, &mut path)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// send a whole region of parts through a workflow, noting which rules see any of them and collecting the
+    /// parts that are accepted. only safe once we know there are no loops
+    fn flow(
+        &self,
+        workflow: usize,
+        mut region: Region,
+        reached: &mut Vec<Vec<bool>>,
+        accepted: &mut Vec<Region>,
+    ) {
+        for (i, rule) in self.workflows[workflow].rules.iter().enumerate() {
+            if region.is_empty() {
+                break;
+            }
+            reached[workflow][i] = true;
+            let (matched, rest) = match rule.condition {
+                Some(c) => c.split(&region),
+                None => (region, Cuboid([Interval::new(0, 0); 4])),
+            };
+            if !matched.is_empty() {
+                match rule.target {
+                    Target::Accept => accepted.push(matched),
+                    Target::Reject => {}
+                    Target::Workflow(to) => self.flow(to, matched, reached, accepted),
+                }
+            }
+            region = rest;
+        }
+    }
+
+    pub fn workflow(&self, name: &str) -> Option<&Workflow> {
+        self.ids.get(name).map(|i| &self.workflows[*i])
+    }
+
+    /// a rule as it would be written, like `a<2006:qkq`
+    pub fn describe(&self, rule: &Rule) -> String {
+        let target = match rule.target {
+            Target::Accept => "A",
+            Target::Reject => "R",
+            Target::Workflow(i) => &self.workflows[i].name,
+        };
+        match rule.condition {
+            Some(c) => format!("{}:{}", c, target),
+            None => target.to_string(),
+        }
+    }
+
+    /// every rule a part is checked against, and whether it ends up accepted
+    pub fn explain(&self, part: &Part) -> Explanation {
+        let mut steps = vec![];
+        let mut at = self.start;
+        loop {
+            let w = &self.workflows[at];
+            // validation guarantees the last rule matches everything
+            let rule = w
+                .rules
+                .iter()
+                .find(|r| {
+                    let matched = r.condition.map_or(true, |c| c.matches(part));
+                    steps.push(Step {
+                        workflow: w.name.clone(),
+                        rule: self.describe(r),
+                        matched,
+                    });
+                    matched
+                })
+                .unwrap();
+            match rule.target {
+                Target::Accept => {
+                    return Explanation {
+                        steps,
+                        accepted: true,
+                    }
+                }
+                Target::Reject => {
+                    return Explanation {
+                        steps,
+                        accepted: false,
+                    }
+                }
+                Target::Workflow(to) => at = to,
+            }
+        }
+    }
+
+    pub fn accepts(&self, part: &Part) -> bool {
+        self.explain(part).accepted
+    }
+
+    /// every part that would be accepted, as boxes that don't overlap
+    pub fn accepted_region(&self) -> Vec<Region> {
+        let mut reached = self
+            .workflows
+            .iter()
+            .map(|w| vec![false; w.rules.len()])
+            .collect();
+        let mut accepted = vec![];
+        self.flow(self.start, all_parts(), &mut reached, &mut accepted);
+        accepted
+    }
+
+    /// how many of all possible parts would be accepted
+    pub fn count_accepted(&self) -> usize {
+        self.accepted_region().iter().map(|r| r.volume()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_part, Workflows};
+    use rstest::rstest;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}";
+
+    #[rstest]
+    #[case("{x=787,m=2655,a=1222,s=2876}", "in -> qqz -> qs -> lnx -> A", true)]
+    #[case("{x=1679,m=44,a=2067,s=496}", "in -> px -> rfg -> gd -> R", false)]
+    #[case("{x=2036,m=264,a=79,s=2244}", "in -> qqz -> hdj -> pv -> A", true)]
+    #[case("{x=2461,m=1339,a=466,s=291}", "in -> px -> qkq -> crn -> R", false)]
+    #[case("{x=2127,m=1623,a=2188,s=1013}", "in -> px -> rfg -> A", true)]
+    fn validate_explain(#[case] part: &str, #[case] route: &str, #[case] accepted: bool) {
+        let workflows = Workflows::parse(EXAMPLE).unwrap();
+        let explained = workflows.explain(&parse_part(part).unwrap());
+
+        assert_eq!(route, explained.route());
+        assert_eq!(accepted, explained.accepted);
+    }
+
+    #[test]
+    fn validate_explain_steps() {
+        let workflows = Workflows::parse(EXAMPLE).unwrap();
+        let explained = workflows.explain(&parse_part("{x=2036,m=264,a=79,s=2244}").unwrap());
+
+        assert_eq!(
+            "in: s<1351:px skipped
+in: qqz matched
+qqz: s>2770:qs skipped
+qqz: m<1801:hdj matched
+hdj: m>838:A skipped
+hdj: pv matched
+pv: a>1716:R skipped
+pv: A matched
+accepted",
+            explained.to_string()
+        );
+    }
+
+    #[test]
+    fn validate_accepted_region() {
+        let workflows = Workflows::parse(EXAMPLE).unwrap();
+        let region = workflows.accepted_region();
+
+        assert_eq!(167_409_079_868_000, workflows.count_accepted());
+        // the boxes don't overlap, and agree with checking parts one at a time
+        for (i, a) in region.iter().enumerate() {
+            assert!(region[i + 1..].iter().all(|b| a.intersection(b).is_empty()));
+        }
+        for part in [
+            [787, 2655, 1222, 2876],
+            [1679, 44, 2067, 496],
+            [4000, 1, 1, 4000],
+        ] {
+            assert_eq!(
+                workflows.accepts(&part),
+                region.iter().any(|r| r.contains(&part))
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(
+        "in{x<10:A,zz}",
+        "workflow 'in' sends parts to 'zz', which doesn't exist"
+    )]
+    #[case(
+        "in{x<10:a,R}\na{b}\nb{m>5:a,A}",
+        "parts can go round in a loop: a -> b -> a"
+    )]
+    #[case("in{A,R}", "no part can ever reach these rules: in: R")]
+    // everything left after the first rule matches the second
+    #[case("in{x<10:A,x>4:R,A}", "no part can ever reach these rules: in: A")]
+    #[case("in{A}\nlost{A}", "no part can ever reach these rules: lost: A")]
+    #[case(
+        "in{x<10:A}",
+        "workflow 'in' has no rule for parts that match nothing else"
+    )]
+    #[case("px{A}", "there's no 'in' workflow")]
+    #[case("in{A}\nin{R}", "workflow 'in' is declared twice")]
+    #[case("in{y<10:A,R}", "unknown category 'y'")]
+    fn validate_errors(#[case] input: &str, #[case] expected: &str) {
+        let err = Workflows::parse(input).unwrap_err();
+
+        assert_eq!(expected, err.to_string());
+    }
+}